@@ -0,0 +1,226 @@
+use glam::Mat3;
+
+use crate::rotation::safe_acos;
+use crate::svd::{apply_eigen, Symmetric3x3};
+
+/// Propagates a covariance through a linear map, computing A * Σ * A^T
+///
+/// Only the symmetric entries are computed (see [`Symmetric3x3::congruence_transform`]), so
+/// the result is free of the asymmetry that floating point roundoff introduces in the dense product.
+pub fn transform_covariance(a: &Mat3, cov: &Symmetric3x3) -> Symmetric3x3 {
+    cov.congruence_transform(a)
+}
+
+/// Interpolates between two symmetric positive definite matrices with the log-Euclidean mean
+///
+/// Computes `exp((1 - t) * log(a) + t * log(b))`, which stays positive definite for any `t`
+/// unlike a linear interpolation. Eigenvalues are clamped to the smallest positive float before
+/// taking the logarithm, so semi-definite inputs do not produce NaN.
+pub fn interpolate_spd(a: &Symmetric3x3, b: &Symmetric3x3, t: f32) -> Symmetric3x3 {
+    let log = |x: f32| x.max(f32::MIN_POSITIVE).ln();
+    let log_a = apply_eigen(a, log).to_mat3x3();
+    let log_b = apply_eigen(b, log).to_mat3x3();
+    let mean = Symmetric3x3::from_mat3x3(&(log_a * (1.0 - t) + log_b * t));
+    apply_eigen(&mean, f32::exp)
+}
+
+/// Splits a symmetric tensor into its isotropic and trace-free (deviatoric) parts
+///
+/// Returns the mean of the diagonal `p` and `s - p * I`. The deviatoric part has zero trace and
+/// its eigenvalues measure the pure anisotropy of the tensor.
+pub fn deviatoric(s: &Symmetric3x3) -> (f32, Symmetric3x3) {
+    let p = s.trace() / 3.0;
+    let mut dev = s.clone();
+    dev.m_00 -= p;
+    dev.m_11 -= p;
+    dev.m_22 -= p;
+    (p, dev)
+}
+
+/// Planarity score of a covariance, `(l2 - l3) / l1` for eigenvalues l1 >= l2 >= l3
+///
+/// The score is 1 for a disc-shaped distribution (l1 = l2, l3 = 0) and 0 for both linear
+/// (l2 = l3) and isotropic ones. It only depends on ratios of the eigenvalues, so it does not
+/// depend on the scale of the covariance.
+///
+/// The eigenvalues are computed in closed form as the roots of the characteristic polynomial,
+/// without the Jacobi sweeps and eigenvectors of [`crate::svd::symmetric_eigen`]. With q the
+/// mean eigenvalue, p the scale of the deviatoric part D and `cos(3 phi) = det(D / p) / 2`,
+/// the trigonometric solution of the cubic gives `l1 = q + 2 p cos(phi)` and
+/// `l2 - l3 = 2 sqrt(3) p sin(phi)`. Like any root of a cubic close to a double root, the
+/// score loses about half of the f32 digits near repeated eigenvalues (e.g. for lines and
+/// discs), where it is accurate to a few 1e-4.
+pub fn planarity_score(s: &Symmetric3x3) -> f32 {
+    let (q, dev) = deviatoric(s);
+    let d = dev.to_upper_triangular();
+    let norm2 =
+        d[0] * d[0] + d[3] * d[3] + d[5] * d[5] + 2.0 * (d[1] * d[1] + d[2] * d[2] + d[4] * d[4]);
+    let p = (norm2 / 6.0).sqrt();
+    if p == 0.0 {
+        return 0.0;
+    }
+
+    let b = Symmetric3x3::from_upper_triangular(&d.map(|x| x / p));
+    let phi = safe_acos(0.5 * b.determinant()) / 3.0;
+    let largest = q + 2.0 * p * phi.cos();
+    if largest <= 0.0 {
+        return 0.0;
+    }
+    (2.0 * 3f32.sqrt() * p * phi.sin() / largest).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+    use crate::svd::symmetric_eigen;
+
+    #[test]
+    fn test_transform_covariance_identity() {
+        let cov = Mat3::from_cols(
+            Vec3::new(2.0, 0.5, 0.1),
+            Vec3::new(0.5, 1.0, 0.2),
+            Vec3::new(0.1, 0.2, 0.5),
+        );
+        let out = transform_covariance(&Mat3::IDENTITY, &Symmetric3x3::from_mat3x3(&cov));
+        assert!(out.to_mat3x3().abs_diff_eq(cov, 1e-6));
+    }
+
+    #[test]
+    fn test_transform_covariance_rotates_axes() {
+        let cov = Symmetric3x3::from_mat3x3(&Mat3::from_diagonal(Vec3::new(4.0, 1.0, 0.25)));
+        let r = Mat3::from_axis_angle(Vec3::new(1.0, 2.0, 3.0).normalize(), 0.7);
+
+        let out = transform_covariance(&r, &cov);
+        let m = out.to_mat3x3();
+        assert_eq!(m, m.transpose());
+
+        let (values, vectors) = symmetric_eigen(&out);
+        assert!(values.abs_diff_eq(Vec3::new(4.0, 1.0, 0.25), 1e-4));
+        for i in 0..3 {
+            let expected = r * Mat3::IDENTITY.col(i);
+            approx::assert_relative_eq!(vectors.col(i).dot(expected).abs(), 1.0, epsilon = 1e-4);
+        }
+    }
+
+    fn eigen_planarity(s: &Symmetric3x3) -> f32 {
+        let (values, _) = symmetric_eigen(s);
+        (values.y - values.z) / values.x
+    }
+
+    #[test]
+    fn test_planarity_score_limits() {
+        let diag = |l: Vec3| Symmetric3x3::from_mat3x3(&Mat3::from_diagonal(l));
+
+        let disc = diag(Vec3::new(2.0, 2.0, 0.0));
+        approx::assert_relative_eq!(planarity_score(&disc), 1.0, epsilon = 1e-3);
+        approx::assert_relative_eq!(
+            planarity_score(&diag(Vec3::new(2.0, 1.0, 0.0))),
+            0.5,
+            epsilon = 1e-6
+        );
+
+        // linear and isotropic distributions are not planar, at any scale
+        for scale in [1e-13, 1e-4, 1.0, 1e6] {
+            let line = diag(Vec3::new(scale, 0.0, 0.0));
+            let cylinder = diag(Vec3::new(scale, 1e-3 * scale, 1e-3 * scale));
+            let isotropic = diag(Vec3::splat(scale));
+            approx::assert_relative_eq!(planarity_score(&line), 0.0, epsilon = 1e-3);
+            approx::assert_relative_eq!(planarity_score(&cylinder), 0.0, epsilon = 1e-3);
+            approx::assert_relative_eq!(planarity_score(&isotropic), 0.0, epsilon = 1e-6);
+            approx::assert_relative_eq!(
+                planarity_score(&diag(Vec3::new(scale, scale, 0.0))),
+                1.0,
+                epsilon = 1e-3
+            );
+        }
+        assert_eq!(planarity_score(&Symmetric3x3::ZERO), 0.0);
+    }
+
+    #[test]
+    fn test_planarity_score_monotonic() {
+        let rotated = |l: Vec3| {
+            let r = Mat3::from_axis_angle(Vec3::new(l.x, -1.0, l.y).normalize(), l.x * 0.3);
+            transform_covariance(&r, &Symmetric3x3::from_mat3x3(&Mat3::from_diagonal(l)))
+        };
+        let check = |sweep: &mut dyn Iterator<Item = Vec3>| {
+            let mut last: Option<(f32, f32)> = None;
+            for l in sweep {
+                let cov = rotated(l);
+                let (score, reference) = (planarity_score(&cov), eigen_planarity(&cov));
+                approx::assert_relative_eq!(score, reference, epsilon = 1e-3);
+
+                // each sweep makes the distribution less planar
+                if let Some((last_score, last_reference)) = last {
+                    assert!(reference < last_reference);
+                    assert!(score < last_score);
+                }
+                last = Some((score, reference));
+            }
+        };
+
+        // thicken the distribution, l3 from 0 to l2
+        for (l1, l2) in [(1.0, 1.0), (4.0, 1.0), (9.0, 3.0)] {
+            check(&mut (0..20).map(|i| Vec3::new(l1, l2, l2 * i as f32 / 20.0)));
+        }
+        // shrink the distribution towards a line, l2 from l1 to 0
+        for (l1, ratio) in [(1.0, 0.0), (4.0, 0.01), (9.0, 0.1)] {
+            check(&mut (0..20).map(|i| {
+                let l2 = l1 * (20 - i) as f32 / 20.0;
+                Vec3::new(l1, l2, ratio * l2)
+            }));
+        }
+    }
+
+    #[test]
+    fn test_interpolate_spd() {
+        let a = transform_covariance(
+            &Mat3::from_rotation_x(0.4),
+            &Symmetric3x3::from_mat3x3(&Mat3::from_diagonal(Vec3::new(4.0, 1.0, 0.1))),
+        );
+        let b = transform_covariance(
+            &Mat3::from_rotation_y(-1.0),
+            &Symmetric3x3::from_mat3x3(&Mat3::from_diagonal(Vec3::new(0.5, 2.0, 3.0))),
+        );
+
+        assert!(interpolate_spd(&a, &b, 0.0)
+            .to_mat3x3()
+            .abs_diff_eq(a.to_mat3x3(), 1e-4));
+        assert!(interpolate_spd(&a, &b, 1.0)
+            .to_mat3x3()
+            .abs_diff_eq(b.to_mat3x3(), 1e-4));
+
+        for t in [0.25, 0.5, 0.75] {
+            let (values, _) = symmetric_eigen(&interpolate_spd(&a, &b, t));
+            assert!(values.z > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_interpolate_spd_commuting() {
+        // for commuting matrices the midpoint is the geometric mean
+        let a = Symmetric3x3::from_mat3x3(&Mat3::from_diagonal(Vec3::new(1.0, 4.0, 9.0)));
+        let b = Symmetric3x3::from_mat3x3(&Mat3::from_diagonal(Vec3::new(4.0, 1.0, 1.0)));
+        let mid = interpolate_spd(&a, &b, 0.5);
+        assert!(mid
+            .to_mat3x3()
+            .abs_diff_eq(Mat3::from_diagonal(Vec3::new(2.0, 2.0, 3.0)), 1e-4));
+    }
+
+    #[test]
+    fn test_deviatoric() {
+        let s = Symmetric3x3::from_upper_triangular(&[4.0, 1.0, 0.5, 3.0, -0.25, 2.0]);
+        let (p, dev) = deviatoric(&s);
+        approx::assert_relative_eq!(p, 3.0);
+        approx::assert_relative_eq!(dev.trace(), 0.0, epsilon = 1e-6);
+
+        let recombined = dev.to_mat3x3() + Mat3::IDENTITY * p;
+        assert!(recombined.abs_diff_eq(s.to_mat3x3(), 1e-6));
+
+        // an isotropic tensor has no deviatoric part
+        let (p, dev) = deviatoric(&Symmetric3x3::IDENTITY);
+        assert_eq!(p, 1.0);
+        assert_eq!(dev.to_mat3x3(), Mat3::ZERO);
+    }
+}
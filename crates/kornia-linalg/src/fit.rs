@@ -0,0 +1,378 @@
+use glam::{Mat3, Vec3};
+
+use crate::rotation::rotation_between;
+use crate::svd::{symmetric_eigen, Symmetric3x3};
+
+/// Accumulates the weighted centroid and weighted covariance of a set of points
+///
+/// Returns the centroid and the covariance normalized by the sum of the weights.
+/// If the weights sum to zero the centroid and covariance are zero.
+///
+/// # Panics
+///
+/// Panics if `points` and `weights` do not have the same length.
+pub fn weighted_covariance(points: &[Vec3], weights: &[f32]) -> (Vec3, Symmetric3x3) {
+    assert_eq!(points.len(), weights.len());
+
+    let mut sum_w = 0.0;
+    let mut centroid = Vec3::ZERO;
+    for (p, w) in points.iter().zip(weights) {
+        centroid += *w * *p;
+        sum_w += *w;
+    }
+
+    if sum_w <= 0.0 {
+        return (Vec3::ZERO, Symmetric3x3::ZERO);
+    }
+    centroid /= sum_w;
+
+    (
+        centroid,
+        weighted_covariance_about(points, weights, centroid),
+    )
+}
+
+/// Accumulates the weighted covariance of a set of points about a given center
+///
+/// The covariance is normalized by the sum of the weights, and is zero if they sum to zero.
+///
+/// # Panics
+///
+/// Panics if `points` and `weights` do not have the same length.
+pub fn weighted_covariance_about(points: &[Vec3], weights: &[f32], center: Vec3) -> Symmetric3x3 {
+    assert_eq!(points.len(), weights.len());
+
+    let mut sum_w = 0.0;
+    let mut cov = Mat3::ZERO;
+    for (p, w) in points.iter().zip(weights) {
+        let d = *p - center;
+        cov += Mat3::from_cols(d * d.x, d * d.y, d * d.z) * *w;
+        sum_w += *w;
+    }
+
+    if sum_w <= 0.0 {
+        return Symmetric3x3::ZERO;
+    }
+    Symmetric3x3::from_mat3x3(&(cov / sum_w))
+}
+
+#[derive(Debug, Clone)]
+/// Result of a principal component analysis of a point set
+pub struct PcaResult {
+    /// The center of the points.
+    mean: Vec3,
+
+    /// The covariance of the points about the center.
+    covariance: Symmetric3x3,
+
+    /// The variances along the principal axes in descending order.
+    variances: Vec3,
+
+    /// The principal axes as the columns of the matrix, matching the variances.
+    axes: Mat3,
+}
+
+impl PcaResult {
+    fn from_covariance(mean: Vec3, covariance: Symmetric3x3) -> Self {
+        let (variances, axes) = symmetric_eigen(&covariance);
+        PcaResult {
+            mean,
+            covariance,
+            variances,
+            axes,
+        }
+    }
+
+    /// Get the center of the points.
+    #[inline]
+    pub fn mean(&self) -> Vec3 {
+        self.mean
+    }
+
+    /// Get the covariance of the points about the center.
+    #[inline]
+    pub fn covariance(&self) -> &Symmetric3x3 {
+        &self.covariance
+    }
+
+    /// Get the variances along the principal axes in descending order.
+    #[inline]
+    pub fn variances(&self) -> Vec3 {
+        self.variances
+    }
+
+    /// Get the principal axes as the columns of the matrix.
+    #[inline]
+    pub fn axes(&self) -> &Mat3 {
+        &self.axes
+    }
+}
+
+/// Computes the principal component analysis of a set of points
+pub fn pca(points: &[Vec3]) -> PcaResult {
+    let (mean, cov) = weighted_covariance(points, &vec![1.0; points.len()]);
+    PcaResult::from_covariance(mean, cov)
+}
+
+/// Computes a principal component analysis that is robust to outliers
+///
+/// The center is the geometric median computed with `iters` Weiszfeld iterations, and the
+/// covariance about it weights each point with the Huber weight of its distance to the center,
+/// so that points further than `delta` contribute less.
+pub fn robust_pca(points: &[Vec3], iters: usize, delta: f32) -> PcaResult {
+    let center = geometric_median(points, iters);
+    let weights = points
+        .iter()
+        .map(|p| huber_weight(p.distance(center), delta))
+        .collect::<Vec<_>>();
+    PcaResult::from_covariance(center, weighted_covariance_about(points, &weights, center))
+}
+
+/// Computes the geometric median of a set of points with the Weiszfeld algorithm
+///
+/// Starts from the centroid and stops early once the update is negligible.
+pub fn geometric_median(points: &[Vec3], iters: usize) -> Vec3 {
+    if points.is_empty() {
+        return Vec3::ZERO;
+    }
+
+    let mut median = points.iter().copied().sum::<Vec3>() / points.len() as f32;
+    for _ in 0..iters {
+        let mut num = Vec3::ZERO;
+        let mut den = 0.0;
+        for p in points {
+            // avoid the singularity when the estimate lands on a point
+            let w = 1.0 / p.distance(median).max(1e-6);
+            num += w * *p;
+            den += w;
+        }
+
+        let next = num / den;
+        let step = next.distance(median);
+        median = next;
+        if step < 1e-6 {
+            break;
+        }
+    }
+
+    median
+}
+
+/// Fits a plane to a set of points minimizing the orthogonal distances (total least squares)
+///
+/// Returns a point on the plane (the centroid) and the unit plane normal.
+pub fn fit_plane(points: &[Vec3]) -> (Vec3, Vec3) {
+    let weights = vec![1.0; points.len()];
+    fit_plane_weighted(points, &weights)
+}
+
+/// Fits a plane robustly using iteratively reweighted least squares with Huber weights
+///
+/// Each iteration weights the points by their orthogonal distance `r` to the current plane,
+/// using `1` if `r <= delta` and `delta / r` otherwise, and refits the plane.
+/// Returns a point on the plane and the unit plane normal.
+pub fn fit_plane_robust(points: &[Vec3], iters: usize, delta: f32) -> (Vec3, Vec3) {
+    let mut weights = vec![1.0; points.len()];
+    let (mut centroid, mut normal) = fit_plane_weighted(points, &weights);
+
+    for _ in 0..iters {
+        for (p, w) in points.iter().zip(weights.iter_mut()) {
+            *w = huber_weight((*p - centroid).dot(normal).abs(), delta);
+        }
+        (centroid, normal) = fit_plane_weighted(points, &weights);
+    }
+
+    (centroid, normal)
+}
+
+/// Computes the rigid transform that flattens a point set onto its best-fit plane
+///
+/// Returns (R, t) such that `R * p + t` moves the centroid to the origin and rotates the plane
+/// normal from [`fit_plane`] onto the z-axis, so the transformed points have near-zero z and
+/// their x and y coordinates can be used as 2D coordinates in the plane.
+pub fn best_fit_plane_transform(points: &[Vec3]) -> (Mat3, Vec3) {
+    let (centroid, normal) = fit_plane(points);
+    let r = rotation_between(normal, Vec3::Z);
+    (r, -r.mul_vec3(centroid))
+}
+
+/// Huber weight of a residual with absolute value `r`
+pub(crate) fn huber_weight(r: f32, delta: f32) -> f32 {
+    if r <= delta {
+        1.0
+    } else {
+        delta / r
+    }
+}
+
+/// Fits a plane to weighted points, the normal is the eigenvector of the smallest eigenvalue
+fn fit_plane_weighted(points: &[Vec3], weights: &[f32]) -> (Vec3, Vec3) {
+    let (centroid, cov) = weighted_covariance(points, weights);
+    let (_, vectors) = symmetric_eigen(&cov);
+    (centroid, vectors.z_axis.normalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plane_points() -> Vec<Vec3> {
+        let mut points = Vec::new();
+        for i in 0..15 {
+            for j in 0..15 {
+                let x = i as f32 * 0.2 - 1.5;
+                let y = j as f32 * 0.2 - 1.5;
+                points.push(Vec3::new(x, y, 0.1 * x - 0.2 * y + 1.0));
+            }
+        }
+        points
+    }
+
+    fn same_normal(a: Vec3, b: Vec3, tol: f32) -> bool {
+        1.0 - a.dot(b).abs() < tol
+    }
+
+    #[test]
+    fn test_weighted_covariance() {
+        let points = [Vec3::new(1.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0)];
+        let (centroid, cov) = weighted_covariance(&points, &[1.0, 1.0]);
+        assert_eq!(centroid, Vec3::ZERO);
+        assert!(cov
+            .to_mat3x3()
+            .abs_diff_eq(Mat3::from_diagonal(Vec3::new(1.0, 0.0, 0.0)), 1e-6));
+
+        let (centroid, _) = weighted_covariance(&points, &[3.0, 1.0]);
+        assert!(centroid.abs_diff_eq(Vec3::new(0.5, 0.0, 0.0), 1e-6));
+    }
+
+    #[test]
+    fn test_fit_plane() {
+        let points = plane_points();
+        let (centroid, normal) = fit_plane(&points);
+        let expected = Vec3::new(0.1, -0.2, -1.0).normalize();
+        assert!(same_normal(normal, expected, 1e-5));
+        for p in &points {
+            assert!((*p - centroid).dot(normal).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_fit_plane_robust_outliers() {
+        let clean = plane_points();
+        let (_, clean_normal) = fit_plane(&clean);
+
+        // plant an off-plane cluster
+        let mut points = clean.clone();
+        for i in 0..20 {
+            let x = 1.0 + i as f32 * 0.01;
+            points.push(Vec3::new(x, 1.0, 4.0 + 0.05 * i as f32));
+        }
+
+        let (_, naive_normal) = fit_plane(&points);
+        let (centroid, normal) = fit_plane_robust(&points, 20, 0.05);
+
+        assert!(!same_normal(naive_normal, clean_normal, 1e-2));
+        assert!(same_normal(normal, clean_normal, 1e-3));
+
+        // the outliers end up far from the recovered plane and get a small weight
+        for p in &points[clean.len()..] {
+            let r = (*p - centroid).dot(normal).abs();
+            assert!(huber_weight(r, 0.05) < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_pca() {
+        let r = Mat3::from_rotation_z(0.5);
+        let points = [
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(-2.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+        ]
+        .map(|p| r * p + Vec3::new(1.0, 2.0, 3.0));
+
+        let result = pca(&points);
+        assert!(result.mean().abs_diff_eq(Vec3::new(1.0, 2.0, 3.0), 1e-5));
+        assert!(result
+            .variances()
+            .abs_diff_eq(Vec3::new(2.0, 0.5, 0.0), 1e-4));
+        approx::assert_relative_eq!(
+            result.axes().x_axis.dot(r * Vec3::X).abs(),
+            1.0,
+            epsilon = 1e-4
+        );
+    }
+
+    #[test]
+    fn test_geometric_median() {
+        let points = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(100.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+        ];
+        let median = geometric_median(&points, 100);
+        assert!(median.abs_diff_eq(Vec3::new(1.0, 0.0, 0.0), 1e-2));
+    }
+
+    #[test]
+    fn test_robust_pca_outliers() {
+        let mut points = Vec::new();
+        for i in 0..20 {
+            for j in 0..10 {
+                let x = i as f32 * 0.5 - 4.75;
+                let y = j as f32 * 0.2 - 0.9;
+                points.push(Vec3::new(x, y, 0.0));
+            }
+        }
+        let clean = pca(&points);
+
+        for i in 0..8 {
+            points.push(Vec3::new(0.1 * i as f32, 0.0, 30.0));
+        }
+        let plain = pca(&points);
+        let robust = robust_pca(&points, 50, 1.0);
+
+        let plain_shift = plain.mean().distance(clean.mean());
+        let robust_shift = robust.mean().distance(clean.mean());
+        assert!(robust_shift < 0.2 * plain_shift);
+
+        // the outliers take over the principal axis of the plain pca only
+        assert!(plain.axes().x_axis.dot(Vec3::X).abs() < 0.5);
+        assert!(robust.axes().x_axis.dot(Vec3::X).abs() > 0.99);
+    }
+
+    #[test]
+    fn test_best_fit_plane_transform() {
+        let mut points = plane_points();
+        // small out-of-plane noise
+        for (i, p) in points.iter_mut().enumerate() {
+            p.z += 0.01 * ((i * 7) % 5) as f32 - 0.02;
+        }
+
+        let (r, t) = best_fit_plane_transform(&points);
+        let flat: Vec<Vec3> = points.iter().map(|p| r * *p + t).collect();
+
+        let mean_z = flat.iter().map(|p| p.z).sum::<f32>() / flat.len() as f32;
+        let var_z = flat.iter().map(|p| (p.z - mean_z).powi(2)).sum::<f32>() / flat.len() as f32;
+        let var_x = flat.iter().map(|p| p.x * p.x).sum::<f32>() / flat.len() as f32;
+        approx::assert_relative_eq!(mean_z, 0.0, epsilon = 1e-4);
+        assert!(var_z < 1e-3);
+        assert!(var_x > 0.1);
+
+        // exactly planar points end up at z = 0
+        let (r, t) = best_fit_plane_transform(&plane_points());
+        for p in plane_points() {
+            assert!((r * p + t).z.abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_weighted_covariance_length_mismatch() {
+        weighted_covariance(&[Vec3::X, Vec3::Y, Vec3::Z], &[1.0, 1.0]);
+    }
+}
@@ -0,0 +1,148 @@
+use glam::{Mat3, Vec2};
+
+use crate::mat3::is_singular;
+
+/// Applies a homography to a 2D point and dehomogenizes the result
+///
+/// Points mapped onto the line at infinity have no finite image and are returned as
+/// `Vec2::NAN`. Since homographies are defined up to scale, this is detected relative to the
+/// mapped point: |w| at most `f32::EPSILON` times the norm of the homogeneous point (x, y, w).
+pub fn warp_point(h: &Mat3, p: Vec2) -> Vec2 {
+    let q = h.mul_vec3(p.extend(1.0));
+    if q.z.abs() <= f32::EPSILON * q.length() {
+        return Vec2::NAN;
+    }
+    Vec2::new(q.x / q.z, q.y / q.z)
+}
+
+/// Applies a homography to a batch of 2D points, see [`warp_point`]
+///
+/// # Panics
+///
+/// Panics if `pts` and `out` do not have the same length.
+pub fn warp_points(h: &Mat3, pts: &[Vec2], out: &mut [Vec2]) {
+    assert_eq!(pts.len(), out.len());
+    for (o, p) in out.iter_mut().zip(pts) {
+        *o = warp_point(h, *p);
+    }
+}
+
+/// Inverts a homography and normalizes it so that its bottom-right entry is 1
+///
+/// Returns `None` if the homography is singular. Since homographies are defined up to scale,
+/// singularity is checked relative to the norms of the columns, so that large pixel
+/// translations are accepted. The normalization is skipped if the bottom-right entry of the
+/// inverse is zero.
+pub fn inverse_homography(h: &Mat3) -> Option<Mat3> {
+    if is_singular(h) {
+        return None;
+    }
+
+    let inv = h.inverse();
+    if inv.z_axis.z == 0.0 {
+        return Some(inv);
+    }
+    Some(inv / inv.z_axis.z)
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+
+    #[test]
+    fn test_warp_point_identity() {
+        let p = Vec2::new(3.5, -2.0);
+        assert_eq!(warp_point(&Mat3::IDENTITY, p), p);
+    }
+
+    #[test]
+    fn test_warp_point_known() {
+        // row-major [[2, 0, 1], [0, 1, -1], [0, 0.5, 1]]
+        let h = Mat3::from_cols(
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.5),
+            Vec3::new(1.0, -1.0, 1.0),
+        );
+        // (x, y) = (1, 2) -> (2 + 1, 2 - 1, 1 + 1) / 2
+        assert!(warp_point(&h, Vec2::new(1.0, 2.0)).abs_diff_eq(Vec2::new(1.5, 0.5), 1e-6));
+
+        // y = -2 maps to the line at infinity
+        assert!(warp_point(&h, Vec2::new(0.0, -2.0)).is_nan());
+
+        // the result does not depend on the scale of the homography
+        for scale in [1e-8, 1e8] {
+            let p = warp_point(&(h * scale), Vec2::new(1.0, 2.0));
+            assert!(p.abs_diff_eq(Vec2::new(1.5, 0.5), 1e-6));
+            assert!(warp_point(&(h * scale), Vec2::new(0.0, -2.0)).is_nan());
+        }
+        assert!(warp_point(&(Mat3::IDENTITY * 1e-8), Vec2::new(3.0, 4.0))
+            .abs_diff_eq(Vec2::new(3.0, 4.0), 1e-6));
+    }
+
+    #[test]
+    fn test_warp_points() {
+        let h = Mat3::from_translation(Vec2::new(1.0, -2.0));
+        let pts = [Vec2::ZERO, Vec2::new(1.0, 1.0), Vec2::new(-3.0, 4.0)];
+        let mut out = [Vec2::ZERO; 3];
+
+        warp_points(&h, &pts, &mut out);
+        for (o, p) in out.iter().zip(&pts) {
+            assert_eq!(*o, *p + Vec2::new(1.0, -2.0));
+        }
+    }
+
+    #[test]
+    fn test_inverse_homography() {
+        let h = Mat3::from_cols(
+            Vec3::new(1.2, 0.1, 0.001),
+            Vec3::new(-0.2, 0.9, 0.002),
+            Vec3::new(10.0, -5.0, 2.0),
+        );
+        let inv = inverse_homography(&h).unwrap();
+        assert_eq!(inv.z_axis.z, 1.0);
+
+        for p in [Vec2::ZERO, Vec2::new(100.0, 50.0), Vec2::new(-30.0, 250.0)] {
+            let q = warp_point(&inv, warp_point(&h, p));
+            assert!(q.abs_diff_eq(p, 1e-3));
+        }
+
+        // the result does not depend on the scale of the homography
+        for scale in [1e-8, 1e8] {
+            let scaled = inverse_homography(&(h * scale)).unwrap();
+            assert_eq!(scaled.z_axis.z, 1.0);
+            assert!(scaled.abs_diff_eq(inv, 1e-5));
+        }
+    }
+
+    #[test]
+    fn test_inverse_homography_singular() {
+        let h = Mat3::from_cols(
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(2.0, 4.0, 6.0),
+            Vec3::new(0.0, 1.0, 1.0),
+        );
+        assert!(inverse_homography(&h).is_none());
+        assert!(inverse_homography(&Mat3::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_inverse_homography_pixel_space() {
+        // row-major [[1, 0, 500], [0, 1, 300], [0, 0, 1]]
+        let translation = Mat3::from_translation(Vec2::new(500.0, 300.0));
+        let inv = inverse_homography(&translation).unwrap();
+        assert!(inv.abs_diff_eq(Mat3::from_translation(Vec2::new(-500.0, -300.0)), 1e-4));
+
+        // similarity about the centre of a 640x480 image
+        let centre = Vec2::new(320.0, 240.0);
+        let h = Mat3::from_translation(centre)
+            * Mat3::from_scale_angle_translation(Vec2::splat(0.8), 0.3, Vec2::ZERO)
+            * Mat3::from_translation(-centre);
+        let inv = inverse_homography(&h).unwrap();
+        for p in [Vec2::ZERO, Vec2::new(640.0, 480.0), Vec2::new(100.0, 400.0)] {
+            let q = warp_point(&inv, warp_point(&h, p));
+            assert!(q.abs_diff_eq(p, 1e-2));
+        }
+    }
+}
@@ -1,5 +1,27 @@
 #![deny(missing_docs)]
 #![doc = env!("CARGO_PKG_DESCRIPTION")]
 
+/// Module to propagate and manipulate 3x3 covariance matrices
+pub mod covariance;
+
+/// Module to fit geometric primitives to point sets
+pub mod fit;
+
+/// Module to apply and manipulate planar homographies
+pub mod homography;
+
+/// Module with helpers for 3x3 matrices
+pub mod mat3;
+
+/// Module to convert rigid transforms to and from homogeneous matrices
+pub mod rigid;
+
+/// Module with helpers to build and compare rotation matrices
+pub mod rotation;
+
+/// serde module containing the serialization and deserialization utilities.
+#[cfg(feature = "serde")]
+pub mod serde;
+
 /// Module to calculate SVD of a 3x3 matrix
 pub mod svd;
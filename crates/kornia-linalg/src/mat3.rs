@@ -0,0 +1,317 @@
+use glam::{Mat3, Vec3};
+
+use crate::svd::Symmetric3x3;
+
+/// Creates a diagonal 3x3 matrix, usable in `const` and `static` contexts
+pub const fn mat3_from_diagonal(v: [f32; 3]) -> Mat3 {
+    Mat3::from_cols(
+        Vec3::new(v[0], 0.0, 0.0),
+        Vec3::new(0.0, v[1], 0.0),
+        Vec3::new(0.0, 0.0, v[2]),
+    )
+}
+
+/// Checks that no entry of the matrix is NaN or infinite
+pub fn mat3_is_finite(m: &Mat3) -> bool {
+    m.is_finite()
+}
+
+/// Checks that no entry of the symmetric matrix is NaN or infinite
+pub fn sym_is_finite(s: &Symmetric3x3) -> bool {
+    s.to_upper_triangular().iter().all(|x| x.is_finite())
+}
+
+/// Applies a function to every entry of a matrix
+pub fn map_entries<F: Fn(f32) -> f32>(m: &Mat3, f: F) -> Mat3 {
+    Mat3::from_cols_array(&m.to_cols_array().map(f))
+}
+
+/// Folds all the entries of a matrix into an accumulator, visiting them in column-major order
+pub fn fold_entries<A, F: Fn(A, f32) -> A>(m: &Mat3, init: A, f: F) -> A {
+    m.to_cols_array().into_iter().fold(init, f)
+}
+
+/// Approximates the Jacobian of a `Vec3 -> Vec3` function at `x` with central differences
+///
+/// Column j is `(f(x + eps * e_j) - f(x - eps * e_j)) / (2 * eps)`, which is useful to validate
+/// analytic Jacobians. The truncation error is O(eps^2), but `eps` should not be too small
+/// relative to `x` because of f32 cancellation (around 1e-3 is a good default).
+pub fn numerical_jacobian<F: Fn(Vec3) -> Vec3>(f: F, x: Vec3, eps: f32) -> Mat3 {
+    let column = |e: Vec3| (f(x + eps * e) - f(x - eps * e)) / (2.0 * eps);
+    Mat3::from_cols(column(Vec3::X), column(Vec3::Y), column(Vec3::Z))
+}
+
+/// Normalizes the columns of a matrix, returning the normalized matrix and the column norms
+///
+/// Each column is divided by its largest absolute entry before taking the norm, as `hypot`
+/// does, so the result neither overflows nor underflows for columns of extreme magnitude.
+/// Zero columns are left unchanged and have a norm of zero.
+pub fn normalize_columns(m: &Mat3) -> (Mat3, Vec3) {
+    let normalize = |c: Vec3| {
+        let scale = c.abs().max_element();
+        if scale == 0.0 {
+            return (c, 0.0);
+        }
+        let unit = c / scale;
+        let length = unit.length();
+        (unit / length, scale * length)
+    };
+
+    let (x, nx) = normalize(m.x_axis);
+    let (y, ny) = normalize(m.y_axis);
+    let (z, nz) = normalize(m.z_axis);
+    (Mat3::from_cols(x, y, z), Vec3::new(nx, ny, nz))
+}
+
+/// Checks whether a matrix is singular relative to the Hadamard bound |det| <= |c0| |c1| |c2|
+///
+/// The determinant of the column-normalized matrix is compared to `f32::EPSILON`, so the test
+/// does not depend on the scale of the columns, e.g. a homography with a translation of several
+/// hundred pixels is not mistaken for a singular one. Zero or non-finite columns are singular.
+pub(crate) fn is_singular(m: &Mat3) -> bool {
+    let det = normalize_columns(m).0.determinant();
+    det.is_nan() || det.abs() <= f32::EPSILON
+}
+
+/// Computes the signed volume of the parallelepiped spanned by three vectors
+///
+/// Equals the triple product a . (b x c), computed as the product of the diagonal of R in a
+/// Householder QR decomposition of the matrix [a b c], with the sign of det(Q) restored. The
+/// columns are normalized first (see [`normalize_columns`]) and their norms multiplied back,
+/// so vectors of very small or very large magnitude do not lose precision to underflow or
+/// overflow in the intermediate products. For nearly coplanar vectors this is not more
+/// accurate than the triple product: the error of both is a few ulps of the Hadamard bound
+/// |a| |b| |c|, so the relative error grows as the volume vanishes.
+pub fn gram_determinant(a: Vec3, b: Vec3, c: Vec3) -> f32 {
+    let (m, norms) = normalize_columns(&Mat3::from_cols(a, b, c));
+    let (mut c0, mut c1, mut c2) = (m.x_axis, m.y_axis, m.z_axis);
+    let mut sign = 1.0;
+
+    // first reflection maps c0 onto the x axis
+    let alpha = -c0.length().copysign(c0.x);
+    let v = c0 - alpha * Vec3::X;
+    let vv = v.length_squared();
+    if vv > 0.0 {
+        c1 -= (2.0 * v.dot(c1) / vv) * v;
+        c2 -= (2.0 * v.dot(c2) / vv) * v;
+        c0 = alpha * Vec3::X;
+        sign = -sign;
+    }
+
+    // second reflection acts on the last two rows and maps c1 onto the y axis
+    let alpha = -c1.y.hypot(c1.z).copysign(c1.y);
+    let v = Vec3::new(0.0, c1.y - alpha, c1.z);
+    let vv = v.length_squared();
+    if vv > 0.0 {
+        c2 -= (2.0 * v.dot(c2) / vv) * v;
+        c1.y = alpha;
+        sign = -sign;
+    }
+
+    sign * c0.x * c1.y * c2.z * norms.x * norms.y * norms.z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static SCALE: Mat3 = mat3_from_diagonal([500.0, 400.0, 1.0]);
+    static IDENTITY: Symmetric3x3 = Symmetric3x3::IDENTITY;
+
+    #[test]
+    fn test_mat3_from_diagonal() {
+        const M: Mat3 = mat3_from_diagonal([1.0, 2.0, 3.0]);
+        assert_eq!(M, Mat3::from_diagonal(Vec3::new(1.0, 2.0, 3.0)));
+        assert_eq!(SCALE, Mat3::from_diagonal(Vec3::new(500.0, 400.0, 1.0)));
+        assert_eq!(mat3_from_diagonal([1.0; 3]), Mat3::IDENTITY);
+    }
+
+    #[test]
+    fn test_symmetric_identity() {
+        assert_eq!(IDENTITY.to_mat3x3(), Mat3::IDENTITY);
+    }
+
+    #[test]
+    fn test_gram_determinant() {
+        let cases = [
+            (Vec3::X, Vec3::Y, Vec3::Z),
+            (Vec3::Y, Vec3::X, Vec3::Z),
+            (
+                Vec3::new(1.0, 2.0, 3.0),
+                Vec3::new(-2.0, 4.0, 0.5),
+                Vec3::new(3.0, 1.0, 9.0),
+            ),
+            (
+                Vec3::new(0.5, -1.0, 0.0),
+                Vec3::new(2.0, 0.0, 1.0),
+                Vec3::new(0.0, 3.0, -1.5),
+            ),
+        ];
+        for (a, b, c) in cases {
+            let expected = a.dot(b.cross(c));
+            approx::assert_relative_eq!(
+                gram_determinant(a, b, c),
+                expected,
+                epsilon = 1e-4,
+                max_relative = 1e-5
+            );
+        }
+
+        let degenerate = gram_determinant(Vec3::X, Vec3::Y, Vec3::new(1.0, 1.0, 0.0));
+        approx::assert_relative_eq!(degenerate, 0.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_map_entries() {
+        let m = Mat3::from_cols_array(&[1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0, -8.0, 9.0]);
+        assert_eq!(map_entries(&m, |x| x * 2.0), m * 2.0);
+        assert_eq!(
+            map_entries(&m, |x| x.clamp(-1.0, 1.0)).to_cols_array()[..3],
+            [1.0, -1.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_fold_entries() {
+        let m = Mat3::from_cols_array(&[1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0, -8.0, 9.0]);
+        assert_eq!(fold_entries(&m, 0.0, |acc, x| acc + x), 5.0);
+
+        let mut bad = m;
+        bad.y_axis.z = f32::NAN;
+        bad.z_axis.x = f32::INFINITY;
+        let non_finite = fold_entries(&bad, 0, |acc, x| acc + usize::from(!x.is_finite()));
+        assert_eq!(non_finite, 2);
+
+        let order = fold_entries(&m, Vec::new(), |mut acc, x| {
+            acc.push(x);
+            acc
+        });
+        assert_eq!(order, m.to_cols_array());
+    }
+
+    #[test]
+    fn test_is_finite() {
+        let mut m = Mat3::from_rotation_z(0.3);
+        assert!(mat3_is_finite(&m));
+        m.z_axis.y = f32::NAN;
+        assert!(!mat3_is_finite(&m));
+        m.z_axis.y = f32::NEG_INFINITY;
+        assert!(!mat3_is_finite(&m));
+
+        let mut data = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        assert!(sym_is_finite(&Symmetric3x3::from_upper_triangular(&data)));
+        data[4] = f32::NAN;
+        assert!(!sym_is_finite(&Symmetric3x3::from_upper_triangular(&data)));
+    }
+
+    #[test]
+    fn test_numerical_jacobian_linear() {
+        let a = Mat3::from_cols_array(&[1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0, -8.0, 9.0]);
+        let b = Vec3::new(0.5, 1.0, -2.0);
+        let jacobian = numerical_jacobian(|x| a * x + b, Vec3::new(0.3, -1.0, 2.0), 1e-2);
+        assert!(jacobian.abs_diff_eq(a, 1e-3));
+    }
+
+    #[test]
+    fn test_numerical_jacobian_rotation() {
+        // d(exp(w) * p) / dw at w = 0 is -[p]x
+        let p = Vec3::new(1.0, -2.0, 0.5);
+        let jacobian = numerical_jacobian(|w| crate::rotation::so3_exp(w) * p, Vec3::ZERO, 1e-3);
+        let expected = Mat3::from_cols(
+            Vec3::new(0.0, -p.z, p.y),
+            Vec3::new(p.z, 0.0, -p.x),
+            Vec3::new(-p.y, p.x, 0.0),
+        );
+        assert!(jacobian.abs_diff_eq(expected, 1e-3));
+    }
+
+    #[test]
+    fn test_normalize_columns() {
+        let m = Mat3::from_cols(
+            Vec3::new(3.0, 0.0, 4.0),
+            Vec3::new(0.0, -2.0, 0.0),
+            Vec3::ZERO,
+        );
+        let (n, norms) = normalize_columns(&m);
+        assert!(n.abs_diff_eq(
+            Mat3::from_cols(Vec3::new(0.6, 0.0, 0.8), Vec3::NEG_Y, Vec3::ZERO),
+            1e-6
+        ));
+        assert_eq!(norms, Vec3::new(5.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn test_normalize_columns_extreme() {
+        // the squared entries of the first column overflow and those of the second underflow
+        let m = Mat3::from_cols(
+            Vec3::new(3e30, 0.0, -4e30),
+            Vec3::new(1e-30, 1e-30, 0.0),
+            Vec3::new(1.0, 2.0, 2.0),
+        );
+        assert!(!m.x_axis.length().is_finite());
+        assert_eq!(m.y_axis.length(), 0.0);
+
+        let (n, norms) = normalize_columns(&m);
+        assert!(n.is_finite());
+        assert!(n.x_axis.abs_diff_eq(Vec3::new(0.6, 0.0, -0.8), 1e-6));
+        assert!(n
+            .y_axis
+            .abs_diff_eq(Vec3::new(1.0, 1.0, 0.0) / 2f32.sqrt(), 1e-6));
+        approx::assert_relative_eq!(norms.x, 5e30, max_relative = 1e-6);
+        approx::assert_relative_eq!(norms.y, 2f32.sqrt() * 1e-30, max_relative = 1e-6);
+        approx::assert_relative_eq!(norms.z, 3.0, max_relative = 1e-6);
+    }
+
+    #[test]
+    fn test_is_singular() {
+        assert!(!is_singular(&Mat3::IDENTITY));
+        assert!(!is_singular(&Mat3::from_diagonal(Vec3::new(
+            1e4, 1.0, 1e-4
+        ))));
+        assert!(!is_singular(&(Mat3::from_rotation_x(0.3) * 1e-8)));
+
+        assert!(is_singular(&Mat3::ZERO));
+        assert!(is_singular(&Mat3::from_cols(Vec3::X, Vec3::Y, Vec3::ZERO)));
+        assert!(is_singular(&Mat3::from_cols(
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(-2.0, 0.5, 1.0),
+            Vec3::new(-1.0, 2.5, 4.0),
+        )));
+        assert!(is_singular(&Mat3::from_diagonal(Vec3::new(
+            1.0,
+            f32::NAN,
+            1.0
+        ))));
+    }
+
+    #[test]
+    fn test_gram_determinant_near_degenerate() {
+        // c is almost a combination of a and b, compared against a f64 reference
+        let a = Vec3::new(0.3, -0.8, 0.5);
+        let b = Vec3::new(-0.6, 0.1, 0.9);
+        for t in [1e-2, 1e-3, -1e-4] {
+            let c = 0.7 * a - 1.3 * b + t * a.cross(b);
+            let (a64, b64, c64) = (a.as_dvec3(), b.as_dvec3(), c.as_dvec3());
+            let expected = a64.dot(b64.cross(c64));
+            let bound = a64.length() * b64.length() * c64.length();
+
+            let error = (gram_determinant(a, b, c) as f64 - expected).abs();
+            assert!(error <= 4.0 * f32::EPSILON as f64 * bound);
+        }
+    }
+
+    #[test]
+    fn test_gram_determinant_small_magnitude() {
+        let (a, b, c) = (
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(-2.0, 4.0, 0.5),
+            Vec3::new(3.0, 1.0, 9.0),
+        );
+        let expected = a.dot(b.cross(c)) * 1e-21;
+        approx::assert_relative_eq!(
+            gram_determinant(1e-7 * a, 1e-7 * b, 1e-7 * c),
+            expected,
+            max_relative = 1e-5
+        );
+    }
+}
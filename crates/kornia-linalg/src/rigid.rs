@@ -0,0 +1,156 @@
+use glam::{Mat3, Mat4, Vec3};
+
+/// Builds the homogeneous 4x4 matrix [R t; 0 1] from a rotation and a translation
+///
+/// glam stores matrices column-major, so the rotation fills the first three columns and the
+/// translation is written to the last column (`w_axis`).
+pub fn rigid_to_mat4(r: &Mat3, t: Vec3) -> Mat4 {
+    Mat4::from_cols(
+        r.x_axis.extend(0.0),
+        r.y_axis.extend(0.0),
+        r.z_axis.extend(0.0),
+        t.extend(1.0),
+    )
+}
+
+/// Splits a homogeneous 4x4 matrix [R t; 0 1] into its rotation and translation
+///
+/// The rotation is read from the upper-left 3x3 block and the translation from the last column.
+/// The bottom row is assumed to be [0 0 0 1] and is ignored.
+pub fn mat4_to_rigid(m: &Mat4) -> (Mat3, Vec3) {
+    let r = Mat3::from_cols(
+        m.x_axis.truncate(),
+        m.y_axis.truncate(),
+        m.z_axis.truncate(),
+    );
+    (r, m.w_axis.truncate())
+}
+
+/// Computes the transform taking pose `a` to pose `b`, i.e. a^-1 * b
+///
+/// Poses are given as (R, t) mapping frame points to world points as `R * p + t`, so that
+/// composing `a` with the result yields `b`.
+pub fn relative_transform((ra, ta): (Mat3, Vec3), (rb, tb): (Mat3, Vec3)) -> (Mat3, Vec3) {
+    let ra_t = ra.transpose();
+    (ra_t.mul_mat3(&rb), ra_t.mul_vec3(tb - ta))
+}
+
+/// Counts the correspondences that triangulate in front of both cameras (cheirality check)
+///
+/// The second camera is related to the first by `x2 = R * x1 + t`, and each correspondence is
+/// a pair of rays (e.g. normalized image coordinates `[u, v, 1]`) in the first and second camera.
+/// Each pair is triangulated by finding the depths that bring both rays closest to each other,
+/// and counted if the point has positive depth in both views. This is the score used to pick
+/// the right pose among the four candidates of an essential matrix decomposition.
+pub fn count_in_front(r: &Mat3, t: Vec3, correspondences: &[(Vec3, Vec3)]) -> usize {
+    correspondences
+        .iter()
+        .filter(|(x1, x2)| match triangulate_depths(r, t, *x1, *x2) {
+            Some((d1, d2)) => d1 * x1.z > 0.0 && d2 * x2.z > 0.0,
+            None => false,
+        })
+        .count()
+}
+
+/// Solves `d2 * x2 = d1 * R * x1 + t` in the least squares sense for the depths along both rays
+///
+/// Returns `None` for (nearly) parallel rays, which carry no depth information.
+fn triangulate_depths(r: &Mat3, t: Vec3, x1: Vec3, x2: Vec3) -> Option<(f32, f32)> {
+    let a = r.mul_vec3(x1);
+    let b = -x2;
+
+    // normal equations of [a b] * [d1 d2]^T = -t
+    let (aa, ab, bb) = (a.dot(a), a.dot(b), b.dot(b));
+    let det = aa * bb - ab * ab;
+    if det.abs() <= 1e-7 * aa * bb {
+        return None;
+    }
+
+    let (at, bt) = (-a.dot(t), -b.dot(t));
+    Some(((bb * at - ab * bt) / det, (aa * bt - ab * at) / det))
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec4;
+
+    use super::*;
+
+    #[test]
+    fn test_rigid_to_mat4_layout() {
+        let r = Mat3::from_rotation_z(std::f32::consts::FRAC_PI_2);
+        let t = Vec3::new(1.0, 2.0, 3.0);
+        let m = rigid_to_mat4(&r, t);
+
+        // row-major view: the translation is the last entry of each of the first three rows
+        assert!(m.row(0).abs_diff_eq(Vec4::new(0.0, -1.0, 0.0, 1.0), 1e-6));
+        assert!(m.row(1).abs_diff_eq(Vec4::new(1.0, 0.0, 0.0, 2.0), 1e-6));
+        assert!(m.row(2).abs_diff_eq(Vec4::new(0.0, 0.0, 1.0, 3.0), 1e-6));
+        assert_eq!(m.row(3), Vec4::new(0.0, 0.0, 0.0, 1.0));
+
+        let p = Vec3::new(0.5, -1.0, 2.0);
+        assert!(m.transform_point3(p).abs_diff_eq(r * p + t, 1e-6));
+    }
+
+    #[test]
+    fn test_rigid_mat4_roundtrip() {
+        let r = Mat3::from_axis_angle(Vec3::new(1.0, -2.0, 0.5).normalize(), 1.2);
+        let t = Vec3::new(-4.0, 0.25, 7.5);
+
+        let (r2, t2) = mat4_to_rigid(&rigid_to_mat4(&r, t));
+        assert_eq!(r2, r);
+        assert_eq!(t2, t);
+    }
+
+    #[test]
+    fn test_relative_transform() {
+        let a = (
+            Mat3::from_axis_angle(Vec3::new(0.0, 1.0, 1.0).normalize(), 0.5),
+            Vec3::new(1.0, -2.0, 0.5),
+        );
+        let b = (Mat3::from_rotation_z(1.3), Vec3::new(-3.0, 0.0, 2.0));
+
+        let (r, t) = relative_transform(a, b);
+        let composed = rigid_to_mat4(&a.0, a.1) * rigid_to_mat4(&r, t);
+        let (rb, tb) = mat4_to_rigid(&composed);
+        assert!(rb.abs_diff_eq(b.0, 1e-6));
+        assert!(tb.abs_diff_eq(b.1, 1e-6));
+    }
+
+    #[test]
+    fn test_count_in_front() {
+        let r = Mat3::from_axis_angle(Vec3::new(0.1, 1.0, 0.2).normalize(), 0.2);
+        let t = Vec3::new(-1.0, 0.1, 0.05);
+
+        let mut correspondences = Vec::new();
+        for i in 0..5 {
+            for j in 0..4 {
+                let p = Vec3::new(i as f32 - 2.0, j as f32 - 1.5, 4.0 + 0.5 * (i + j) as f32);
+                let q = r * p + t;
+                correspondences.push((p / p.z, q / q.z));
+            }
+        }
+        assert_eq!(
+            count_in_front(&r, t, &correspondences),
+            correspondences.len()
+        );
+
+        // the four candidates of the essential matrix [t]x R, the twisted pair rotates by pi
+        // about the baseline
+        let twisted = Mat3::from_axis_angle(t.normalize(), std::f32::consts::PI) * r;
+        let candidates = [(r, t), (r, -t), (twisted, t), (twisted, -t)];
+        let scores = candidates.map(|(r, t)| count_in_front(&r, t, &correspondences));
+        for score in &scores[1..] {
+            assert!(*score < scores[0]);
+        }
+    }
+
+    #[test]
+    fn test_count_in_front_parallel_rays() {
+        let correspondences = [(Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, 1.0))];
+        assert_eq!(
+            count_in_front(&Mat3::IDENTITY, Vec3::new(0.0, 0.0, -1.0), &correspondences),
+            0
+        );
+    }
+}
@@ -0,0 +1,739 @@
+use glam::{Mat3, Quat, Vec3};
+
+use crate::mat3::is_singular;
+use crate::svd::svd3;
+
+/// Tolerance used to decide whether a matrix is a valid rotation
+pub const ROTATION_TOLERANCE: f32 = 1e-4;
+
+/// Checks whether a matrix is a rotation, i.e. orthonormal with determinant +1, up to `tol`
+pub fn is_rotation(m: &Mat3, tol: f32) -> bool {
+    m.transpose().mul_mat3(m).abs_diff_eq(Mat3::IDENTITY, tol)
+        && (m.determinant() - 1.0).abs() <= tol
+}
+
+/// Computes the rotation closest to `a` in the Frobenius norm (orthogonal Procrustes)
+///
+/// The U and V factors returned by the SVD are both proper rotations (a reflection shows up as
+/// a negative last singular value), so U * V^T is already sign-corrected.
+pub fn nearest_rotation(a: &Mat3) -> Mat3 {
+    let svd = svd3(a);
+    svd.u().mul_mat3(&svd.v().transpose())
+}
+
+/// Computes the rotation closest to `a` with the scaled Newton iteration X <- (g X + (g X)^-T) / 2
+///
+/// The iteration converges quadratically to the orthogonal polar factor of `a` without an SVD,
+/// which makes it cheap for re-orthonormalizing nearly orthogonal matrices such as slightly
+/// drifted rotations. Each step uses Higham's determinant scaling g = |det X|^(-1/3), so inputs
+/// far from unit scale converge as fast as nearly orthogonal ones. It stops after `iters`
+/// iterations or once the update is negligible. Singular matrices and matrices with a negative
+/// determinant have no rotation as polar factor, so they fall back to the SVD-based
+/// [`nearest_rotation`].
+pub fn polar_rotation_iterative(a: &Mat3, iters: u32) -> Mat3 {
+    if is_singular(a) || a.determinant() < 0.0 {
+        return nearest_rotation(a);
+    }
+
+    let mut x = *a;
+    for _ in 0..iters {
+        let gamma = x.determinant().abs().cbrt().recip();
+        let next = (x * gamma + x.inverse().transpose() / gamma) * 0.5;
+        let converged = next.abs_diff_eq(x, 1e-7);
+        x = next;
+        if converged {
+            break;
+        }
+    }
+    x
+}
+
+/// Computes the uniform scale and rotation `s * R` closest to `a` in the Frobenius norm
+///
+/// The rotation is U * V^T as in [`nearest_rotation`] and the scale is the mean of the singular
+/// values, the smallest one being negative when `a` contains a reflection (as in Umeyama's method).
+pub fn nearest_similarity(a: &Mat3) -> (f32, Mat3) {
+    let svd = svd3(a);
+    let s = svd.s();
+    let scale = (s.x_axis.x + s.y_axis.y + s.z_axis.z) / 3.0;
+    (scale, svd.u().mul_mat3(&svd.v().transpose()))
+}
+
+/// Computes the rotation angle about `axis` that best aligns `src` onto `dst` in the least squares sense
+///
+/// This is the orthogonal Procrustes problem restricted to rotations about a known axis, e.g.
+/// for a turntable. Writing R(theta) * p with Rodrigues' formula, the objective is maximized at
+/// `atan2(sum (axis x p) . q, sum p_perp . q)`, where `p_perp` is the component of `p`
+/// orthogonal to the axis. The axis does not need to be normalized. The angle is in
+/// [-pi, pi] and follows the right-hand rule about `axis`; it is zero if the problem is
+/// degenerate (zero axis or all points on the axis).
+///
+/// # Panics
+///
+/// Panics if `src` and `dst` do not have the same length.
+pub fn procrustes_about_axis(src: &[Vec3], dst: &[Vec3], axis: Vec3) -> f32 {
+    assert_eq!(src.len(), dst.len());
+    let axis = axis.normalize_or_zero();
+
+    let (sin, cos) = src.iter().zip(dst).fold((0.0, 0.0), |(sin, cos), (p, q)| {
+        let p_perp = *p - axis * axis.dot(*p);
+        (sin + axis.cross(*p).dot(*q), cos + p_perp.dot(*q))
+    });
+    sin.atan2(cos)
+}
+
+/// Computes the minimal rotation taking the direction of `from` onto the direction of `to`
+///
+/// Opposite directions are handled by rotating by pi about an arbitrary perpendicular axis.
+/// The identity is returned if either vector is zero.
+pub fn rotation_between(from: Vec3, to: Vec3) -> Mat3 {
+    match (from.try_normalize(), to.try_normalize()) {
+        (Some(from), Some(to)) => Mat3::from_quat(Quat::from_rotation_arc(from, to)),
+        _ => Mat3::IDENTITY,
+    }
+}
+
+/// Builds a right-handed orthonormal frame whose first column is the direction of `a`
+///
+/// The two other axes are an arbitrary orthonormal completion. A zero (or non-finite) vector
+/// has no direction and yields the identity.
+pub fn complete_frame(a: Vec3) -> Mat3 {
+    let Some(x) = a.try_normalize() else {
+        return Mat3::IDENTITY;
+    };
+    let y = x.any_orthonormal_vector();
+    Mat3::from_cols(x, y, x.cross(y))
+}
+
+/// Builds a right-handed orthonormal frame from two vectors using Gram-Schmidt
+///
+/// The first column is the direction of `a`, the second the component of `b` orthogonal to `a`
+/// and the third their cross product. If `b` is zero or parallel to `a` this falls back to
+/// [`complete_frame`] of `a`.
+pub fn complete_frame_2(a: Vec3, b: Vec3) -> Mat3 {
+    let Some(x) = a.try_normalize() else {
+        return Mat3::IDENTITY;
+    };
+
+    let y = b - b.dot(x) * x;
+    if y.length() <= 1e-6 * b.length() {
+        return complete_frame(a);
+    }
+    match y.try_normalize() {
+        Some(y) => Mat3::from_cols(x, y, x.cross(y)),
+        None => complete_frame(a),
+    }
+}
+
+/// Computes the rotation that maps the orthonormal frame `from` onto the frame `to`, i.e. to * from^T
+///
+/// The columns of each matrix are the frame axes. Inputs that are not rotations within
+/// [`ROTATION_TOLERANCE`] are projected onto the nearest rotation first.
+pub fn rotation_between_frames(from: &Mat3, to: &Mat3) -> Mat3 {
+    let from = if is_rotation(from, ROTATION_TOLERANCE) {
+        *from
+    } else {
+        nearest_rotation(from)
+    };
+    let to = if is_rotation(to, ROTATION_TOLERANCE) {
+        *to
+    } else {
+        nearest_rotation(to)
+    };
+    to.mul_mat3(&from.transpose())
+}
+
+/// Computes the rotation of frame `b` relative to frame `a`, i.e. a^T * b
+pub fn relative_rotation(a: &Mat3, b: &Mat3) -> Mat3 {
+    a.transpose().mul_mat3(b)
+}
+
+/// Computes the chordal distance between two rotations, the Frobenius norm of a - b
+///
+/// For rotations separated by the geodesic angle theta, `||a - b||_F = 2 * sqrt(2) * sin(theta / 2)`,
+/// so the distance is monotonic in the angle while avoiding any trigonometric function.
+pub fn chordal_distance(a: &Mat3, b: &Mat3) -> f32 {
+    let d = *a - *b;
+    (d.x_axis.length_squared() + d.y_axis.length_squared() + d.z_axis.length_squared()).sqrt()
+}
+
+/// Exponential map from the Lie algebra so(3) to a rotation matrix (Rodrigues' formula)
+pub fn so3_exp(omega: Vec3) -> Mat3 {
+    Mat3::from_quat(Quat::from_scaled_axis(omega))
+}
+
+/// Arc cosine with the argument clamped to [-1, 1] so roundoff never produces NaN
+pub(crate) fn safe_acos(x: f32) -> f32 {
+    x.clamp(-1.0, 1.0).acos()
+}
+
+/// Arc sine with the argument clamped to [-1, 1] so roundoff never produces NaN
+pub(crate) fn safe_asin(x: f32) -> f32 {
+    x.clamp(-1.0, 1.0).asin()
+}
+
+/// Computes vee(R - R^T), which equals 2 * sin(theta) * axis for a rotation
+fn skew_part(r: &Mat3) -> Vec3 {
+    Vec3::new(
+        r.y_axis.z - r.z_axis.y,
+        r.z_axis.x - r.x_axis.z,
+        r.x_axis.y - r.y_axis.x,
+    )
+}
+
+/// Computes the rotation angle in [0, pi] of a rotation matrix
+///
+/// Small angles are recovered from the skew-symmetric part with asin, where acos of the trace
+/// would lose precision, and larger angles from the trace with acos.
+pub fn rotation_angle(r: &Mat3) -> f32 {
+    let cos_theta = (r.x_axis.x + r.y_axis.y + r.z_axis.z - 1.0) * 0.5;
+    if cos_theta > 0.9 {
+        safe_asin(0.5 * skew_part(r).length())
+    } else {
+        safe_acos(cos_theta)
+    }
+}
+
+/// Logarithm map from a rotation matrix to its rotation vector (axis * angle) in so(3)
+///
+/// The angle is in [0, pi]. Near pi the axis is recovered from the symmetric part of the matrix
+/// since the skew-symmetric part vanishes.
+pub fn so3_log(r: &Mat3) -> Vec3 {
+    let theta = rotation_angle(r);
+    let skew = skew_part(r);
+
+    if theta < 1e-4 {
+        return 0.5 * skew;
+    }
+
+    if std::f32::consts::PI - theta < 1e-3 {
+        // (R + I) / 2 = axis * axis^T at theta = pi, use its largest column
+        let b = (*r + Mat3::IDENTITY) * 0.5;
+        let k = if b.x_axis.x >= b.y_axis.y && b.x_axis.x >= b.z_axis.z {
+            0
+        } else if b.y_axis.y >= b.z_axis.z {
+            1
+        } else {
+            2
+        };
+        let mut axis = b.col(k).normalize();
+        if axis.dot(skew) < 0.0 {
+            axis = -axis;
+        }
+        return theta * axis;
+    }
+
+    theta / (2.0 * theta.sin()) * skew
+}
+
+/// Maps a rotation to a bin index of a discretized SO(3) for building rotational histograms
+///
+/// The rotation vector (axis * angle, see [`so3_log`]) lies in the ball of radius pi, which is
+/// enclosed in the cube [-pi, pi]^3. The cube is split into `subdivisions` cells per axis and
+/// the index of the cell is `ix + n * (iy + n * iz)`, in the range [0, n^3).
+///
+/// The binning is simple but not uniform: cells do not cover equal volumes of SO(3), cells in
+/// the corners of the cube are never used, and rotations by angles close to pi may land in
+/// opposite bins since `omega` and `-omega` describe the same rotation there.
+///
+/// # Panics
+///
+/// Panics if `subdivisions` is zero.
+pub fn rotation_to_bin(r: &Mat3, subdivisions: usize) -> usize {
+    assert!(subdivisions > 0);
+
+    let n = subdivisions as f32;
+    let omega = so3_log(r);
+    let cell = |x: f32| {
+        let t = (x + std::f32::consts::PI) / (2.0 * std::f32::consts::PI);
+        ((t * n).floor().max(0.0) as usize).min(subdivisions - 1)
+    };
+
+    cell(omega.x) + subdivisions * (cell(omega.y) + subdivisions * cell(omega.z))
+}
+
+/// Computes the geodesic (Karcher) mean of a set of rotations, see [`weighted_rotation_average`]
+pub fn rotation_average(rs: &[Mat3], iters: usize) -> Mat3 {
+    weighted_rotation_average(rs, &vec![1.0; rs.len()], iters)
+}
+
+/// Computes the weighted geodesic mean of a set of rotations
+///
+/// Starting from the rotation with the largest weight, each iteration averages the weighted
+/// residuals in the tangent space of the current estimate and moves along the resulting
+/// direction, stopping early once the update is negligible. Weights are normalized to sum to
+/// one, and the identity is returned if there are no rotations or the weights sum to zero.
+///
+/// # Panics
+///
+/// Panics if `rs` and `weights` do not have the same length.
+pub fn weighted_rotation_average(rs: &[Mat3], weights: &[f32], iters: usize) -> Mat3 {
+    assert_eq!(rs.len(), weights.len());
+
+    let total: f32 = weights.iter().sum();
+    if rs.is_empty() || total <= 0.0 {
+        return Mat3::IDENTITY;
+    }
+
+    let mut best = 0;
+    for (i, w) in weights.iter().enumerate() {
+        if *w > weights[best] {
+            best = i;
+        }
+    }
+    let mut mean = rs[best];
+
+    for _ in 0..iters {
+        let mut delta = Vec3::ZERO;
+        for (r, w) in rs.iter().zip(weights) {
+            delta += (*w / total) * so3_log(&relative_rotation(&mean, r));
+        }
+        mean = mean.mul_mat3(&so3_exp(delta));
+        if delta.length() < 1e-7 {
+            break;
+        }
+    }
+
+    mean
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// A 3x3 matrix known to be a rotation (orthonormal with determinant +1)
+///
+/// Encoding the invariant in the type lets functions skip re-validating their input, and lets
+/// the inverse be computed as a transpose.
+pub struct OrthoBasis(Mat3);
+
+impl OrthoBasis {
+    /// Wraps the matrix if it is a rotation within `tol`, see [`is_rotation`]
+    pub fn try_new(m: Mat3, tol: f32) -> Option<Self> {
+        is_rotation(&m, tol).then_some(OrthoBasis(m))
+    }
+
+    /// Wraps the matrix without checking that it is a rotation
+    ///
+    /// The caller is responsible for the invariant, otherwise the results of the methods are meaningless.
+    pub fn new_unchecked(m: Mat3) -> Self {
+        OrthoBasis(m)
+    }
+
+    /// Get the wrapped rotation matrix.
+    #[inline]
+    pub fn matrix(&self) -> &Mat3 {
+        &self.0
+    }
+
+    /// Transpose of the rotation, which is also a rotation
+    pub fn transpose(&self) -> Self {
+        OrthoBasis(self.0.transpose())
+    }
+
+    /// Inverse of the rotation, computed as its transpose
+    pub fn inverse(&self) -> Self {
+        self.transpose()
+    }
+
+    /// Rotation angle in [0, pi], see [`rotation_angle`]
+    pub fn angle(&self) -> f32 {
+        rotation_angle(&self.0)
+    }
+}
+
+/// Number of integration steps between two re-orthonormalizations of a [`RotationTracker`]
+const RENORMALIZE_INTERVAL: usize = 16;
+
+#[derive(Debug, Clone)]
+/// Tracks an orientation by integrating angular velocities, e.g. from a gyroscope
+///
+/// Every step composes the orientation with `so3_exp(omega * dt)`, and every few steps the
+/// matrix is projected back onto the nearest rotation to fight the drift accumulated by roundoff.
+pub struct RotationTracker {
+    /// The current orientation.
+    orientation: Mat3,
+
+    /// The number of steps since the last re-orthonormalization.
+    steps: usize,
+}
+
+impl Default for RotationTracker {
+    fn default() -> Self {
+        Self::new(Mat3::IDENTITY)
+    }
+}
+
+impl RotationTracker {
+    /// Creates a tracker starting at the given orientation
+    pub fn new(orientation: Mat3) -> Self {
+        RotationTracker {
+            orientation,
+            steps: 0,
+        }
+    }
+
+    /// Integrates a body-frame angular velocity `omega` (rad/s) over `dt` seconds
+    pub fn integrate(&mut self, omega: Vec3, dt: f32) {
+        self.orientation = self.orientation.mul_mat3(&so3_exp(omega * dt));
+        self.steps += 1;
+        if self.steps >= RENORMALIZE_INTERVAL {
+            self.orientation = nearest_rotation(&self.orientation);
+            self.steps = 0;
+        }
+    }
+
+    /// Get the current orientation.
+    #[inline]
+    pub fn orientation(&self) -> Mat3 {
+        self.orientation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+
+    #[test]
+    fn test_is_rotation() {
+        assert!(is_rotation(&Mat3::from_rotation_y(0.3), 1e-5));
+        assert!(!is_rotation(
+            &Mat3::from_diagonal(Vec3::new(1.0, 1.0, -1.0)),
+            1e-5
+        ));
+        assert!(!is_rotation(&(Mat3::from_rotation_y(0.3) * 1.1), 1e-5));
+    }
+
+    #[test]
+    fn test_nearest_rotation() {
+        let r = Mat3::from_axis_angle(Vec3::new(0.3, -1.0, 2.0).normalize(), 0.9);
+        assert!(nearest_rotation(&r).abs_diff_eq(r, 1e-5));
+
+        let noisy = r + Mat3::from_cols(
+            Vec3::new(0.01, -0.02, 0.0),
+            Vec3::new(0.0, 0.015, 0.01),
+            Vec3::new(-0.01, 0.0, 0.02),
+        );
+        let projected = nearest_rotation(&noisy);
+        assert!(is_rotation(&projected, 1e-5));
+        assert!(projected.abs_diff_eq(r, 5e-2));
+    }
+
+    #[test]
+    fn test_rotation_between_frames() {
+        let from = Mat3::from_axis_angle(Vec3::new(1.0, 1.0, 0.0).normalize(), 0.4);
+        let to = Mat3::from_axis_angle(Vec3::new(0.0, -1.0, 3.0).normalize(), 2.1);
+
+        let r = rotation_between_frames(&from, &to);
+        assert!(is_rotation(&r, 1e-5));
+        for i in 0..3 {
+            assert!((r * from.col(i)).abs_diff_eq(to.col(i), 1e-5));
+        }
+    }
+
+    #[test]
+    fn test_rotation_between_frames_renormalizes() {
+        let from = Mat3::from_rotation_x(0.5);
+        let to = Mat3::from_rotation_z(-1.0);
+
+        let r = rotation_between_frames(&(from * 1.05), &to);
+        assert!(is_rotation(&r, 1e-5));
+        assert!(r.abs_diff_eq(to * from.transpose(), 1e-4));
+    }
+
+    #[test]
+    fn test_relative_rotation() {
+        let a = Mat3::from_axis_angle(Vec3::new(1.0, 2.0, -1.0).normalize(), 0.8);
+        let b = Mat3::from_rotation_x(-0.3) * a;
+
+        let rel = relative_rotation(&a, &b);
+        assert!(a.mul_mat3(&rel).abs_diff_eq(b, 1e-6));
+        assert!(relative_rotation(&a, &a).abs_diff_eq(Mat3::IDENTITY, 1e-6));
+    }
+
+    #[test]
+    fn test_so3_exp_log() {
+        let omega = Vec3::new(0.3, -0.8, 0.5);
+        let r = so3_exp(omega);
+        assert!(r.abs_diff_eq(
+            Mat3::from_axis_angle(omega.normalize(), omega.length()),
+            1e-6
+        ));
+        assert!(so3_log(&r).abs_diff_eq(omega, 1e-5));
+
+        assert_eq!(so3_exp(Vec3::ZERO), Mat3::IDENTITY);
+        assert!(so3_log(&Mat3::IDENTITY).abs_diff_eq(Vec3::ZERO, 1e-7));
+
+        let axis = Vec3::new(1.0, 2.0, -2.0).normalize();
+        let near_pi = so3_log(&Mat3::from_axis_angle(axis, std::f32::consts::PI - 1e-4));
+        assert!(near_pi.abs_diff_eq(axis * (std::f32::consts::PI - 1e-4), 1e-3));
+    }
+
+    #[test]
+    fn test_rotation_average() {
+        let base = Mat3::from_rotation_x(0.4);
+        let rs = [
+            base * Mat3::from_rotation_z(0.2),
+            base * Mat3::from_rotation_z(-0.2),
+        ];
+        assert!(rotation_average(&rs, 10).abs_diff_eq(base, 1e-5));
+        assert_eq!(rotation_average(&[], 10), Mat3::IDENTITY);
+    }
+
+    #[test]
+    fn test_weighted_rotation_average() {
+        let rs = [
+            Mat3::from_rotation_x(0.4),
+            Mat3::from_axis_angle(Vec3::new(1.0, 1.0, 0.0).normalize(), -0.6),
+            Mat3::from_rotation_z(1.0),
+        ];
+
+        // a dominant weight returns (approximately) that rotation
+        let avg = weighted_rotation_average(&rs, &[1e-4, 1.0 - 2e-4, 1e-4], 20);
+        assert!(avg.abs_diff_eq(rs[1], 1e-3));
+
+        let exact = weighted_rotation_average(&rs, &[0.0, 0.0, 2.0], 20);
+        assert!(exact.abs_diff_eq(rs[2], 1e-6));
+
+        // unnormalized weights give the same result as normalized ones
+        let a = weighted_rotation_average(&rs, &[1.0, 2.0, 3.0], 20);
+        let b = weighted_rotation_average(&rs, &[10.0, 20.0, 30.0], 20);
+        assert!(a.abs_diff_eq(b, 1e-5));
+        assert!(is_rotation(&a, 1e-5));
+    }
+
+    #[test]
+    fn test_safe_acos_asin() {
+        assert_eq!(safe_acos(1.000_000_1), 0.0);
+        assert_eq!(safe_acos(-1.000_000_1), std::f32::consts::PI);
+        assert_eq!(safe_asin(1.000_000_1), std::f32::consts::FRAC_PI_2);
+        assert_eq!(safe_asin(-1.000_000_1), -std::f32::consts::FRAC_PI_2);
+        assert_eq!(safe_acos(0.5), 0.5f32.acos());
+    }
+
+    #[test]
+    fn test_rotation_angle() {
+        for angle in [0.0, 1e-3, 0.3, 1.5, 2.5, std::f32::consts::PI] {
+            let r = Mat3::from_axis_angle(Vec3::new(-1.0, 0.5, 2.0).normalize(), angle);
+            approx::assert_relative_eq!(rotation_angle(&r), angle, epsilon = 1e-3);
+        }
+
+        // a trace slightly above 3 due to roundoff must not produce NaN
+        let drifted = Mat3::IDENTITY * 1.000_000_1;
+        assert_eq!(rotation_angle(&drifted), 0.0);
+        assert_eq!(so3_log(&drifted), Vec3::ZERO);
+
+        let flipped = Mat3::from_diagonal(Vec3::new(1.000_000_1, -1.000_000_1, -1.000_000_1));
+        assert_eq!(rotation_angle(&flipped), std::f32::consts::PI);
+        assert!(!so3_log(&flipped).is_nan());
+    }
+
+    #[test]
+    fn test_chordal_distance() {
+        let a = Mat3::from_axis_angle(Vec3::new(2.0, -1.0, 0.5).normalize(), 0.7);
+        assert_eq!(chordal_distance(&a, &a), 0.0);
+
+        for theta in [0.1, 1.0, 2.0, std::f32::consts::PI] {
+            let b = a * Mat3::from_rotation_y(theta);
+            let expected = 2.0 * std::f32::consts::SQRT_2 * (0.5 * theta).sin();
+            approx::assert_relative_eq!(chordal_distance(&a, &b), expected, epsilon = 1e-5);
+            approx::assert_relative_eq!(chordal_distance(&b, &a), expected, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_rotation_to_bin() {
+        let n = 8;
+        let decode = |bin: usize| [bin % n, (bin / n) % n, bin / (n * n)];
+
+        assert_eq!(rotation_to_bin(&Mat3::IDENTITY, 1), 0);
+        assert_eq!(decode(rotation_to_bin(&Mat3::IDENTITY, n)), [4, 4, 4]);
+
+        for i in 0..50 {
+            let t = i as f32;
+            let axis = Vec3::new(t.sin(), (1.3 * t).cos(), 0.5).normalize();
+            let angle = 0.06 * t;
+            let r = Mat3::from_axis_angle(axis, angle);
+            let bin = rotation_to_bin(&r, n);
+            assert!(bin < n * n * n);
+
+            // a small perturbation stays in the same or an adjacent cell
+            let nearby = rotation_to_bin(&(r * Mat3::from_rotation_x(1e-3)), n);
+            for (a, b) in decode(bin).iter().zip(decode(nearby)) {
+                assert!(a.abs_diff(b) <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_nearest_similarity() {
+        let r = Mat3::from_axis_angle(Vec3::new(-0.5, 1.0, 1.5).normalize(), 1.1);
+        let (scale, rotation) = nearest_similarity(&(r * 2.5));
+        approx::assert_relative_eq!(scale, 2.5, epsilon = 1e-4);
+        assert!(rotation.abs_diff_eq(r, 1e-5));
+
+        // anisotropic scaling averages the singular values
+        let (scale, rotation) =
+            nearest_similarity(&(r * Mat3::from_diagonal(Vec3::new(1.0, 2.0, 3.0))));
+        approx::assert_relative_eq!(scale, 2.0, epsilon = 1e-4);
+        assert!(rotation.abs_diff_eq(r, 1e-5));
+    }
+
+    #[test]
+    fn test_complete_frame() {
+        for a in [
+            Vec3::X,
+            Vec3::new(0.0, 0.0, -3.0),
+            Vec3::new(1.0, -2.0, 0.5),
+        ] {
+            let frame = complete_frame(a);
+            assert!(is_rotation(&frame, 1e-5));
+            assert!(frame.x_axis.abs_diff_eq(a.normalize(), 1e-6));
+        }
+        assert_eq!(complete_frame(Vec3::ZERO), Mat3::IDENTITY);
+    }
+
+    #[test]
+    fn test_complete_frame_2() {
+        let a = Vec3::new(1.0, 1.0, 0.0);
+        let b = Vec3::new(0.0, 2.0, 0.5);
+        let frame = complete_frame_2(a, b);
+        assert!(is_rotation(&frame, 1e-5));
+        assert!(frame.x_axis.abs_diff_eq(a.normalize(), 1e-6));
+        // b lies in the plane of the first two axes, on the positive side of the second
+        approx::assert_relative_eq!(frame.z_axis.dot(b), 0.0, epsilon = 1e-6);
+        assert!(frame.y_axis.dot(b) > 0.0);
+
+        // degenerate inputs
+        for b in [Vec3::ZERO, a * -2.0] {
+            let frame = complete_frame_2(a, b);
+            assert!(is_rotation(&frame, 1e-5));
+            assert!(frame.x_axis.abs_diff_eq(a.normalize(), 1e-6));
+        }
+        assert_eq!(complete_frame_2(Vec3::ZERO, b), Mat3::IDENTITY);
+    }
+
+    #[test]
+    fn test_rotation_between() {
+        let from = Vec3::new(1.0, 2.0, -0.5);
+        let to = Vec3::new(-3.0, 0.5, 1.0);
+        let r = rotation_between(from, to);
+        assert!(is_rotation(&r, 1e-5));
+        assert!((r * from.normalize()).abs_diff_eq(to.normalize(), 1e-5));
+
+        let flip = rotation_between(Vec3::Z, -Vec3::Z);
+        assert!((flip * Vec3::Z).abs_diff_eq(-Vec3::Z, 1e-6));
+        assert_eq!(rotation_between(Vec3::ZERO, Vec3::X), Mat3::IDENTITY);
+    }
+
+    #[test]
+    fn test_rotation_tracker_full_period() {
+        let period = 2.0;
+        let steps = 1000;
+        let omega = Vec3::new(1.0, -2.0, 0.5).normalize() * (2.0 * std::f32::consts::PI / period);
+
+        let mut tracker = RotationTracker::default();
+        for _ in 0..steps {
+            tracker.integrate(omega, period / steps as f32);
+        }
+        assert!(is_rotation(&tracker.orientation(), 1e-5));
+        assert!(rotation_angle(&tracker.orientation()) < 1e-3);
+    }
+
+    #[test]
+    fn test_rotation_tracker() {
+        let start = Mat3::from_rotation_x(0.3);
+        let mut tracker = RotationTracker::new(start);
+        for _ in 0..10 {
+            tracker.integrate(Vec3::new(0.0, 0.0, 0.5), 0.1);
+        }
+        let expected = start * Mat3::from_rotation_z(0.5);
+        assert!(tracker.orientation().abs_diff_eq(expected, 1e-5));
+    }
+
+    #[test]
+    fn test_ortho_basis() {
+        let r = Mat3::from_axis_angle(Vec3::new(1.0, 0.5, -1.0).normalize(), 0.9);
+        let basis = OrthoBasis::try_new(r, 1e-5).unwrap();
+        assert_eq!(*basis.matrix(), r);
+        assert_eq!(*basis.inverse().matrix(), r.transpose());
+        assert!(basis.inverse().matrix().abs_diff_eq(r.inverse(), 1e-6));
+        approx::assert_relative_eq!(basis.angle(), 0.9, epsilon = 1e-5);
+
+        assert!(OrthoBasis::try_new(r * 1.01, 1e-5).is_none());
+        assert!(
+            OrthoBasis::try_new(Mat3::from_diagonal(Vec3::new(1.0, -1.0, 1.0)), 1e-5).is_none()
+        );
+        assert!(OrthoBasis::try_new(Mat3::from_cols(Vec3::X, Vec3::X, Vec3::Z), 1e-5).is_none());
+
+        let unchecked = OrthoBasis::new_unchecked(Mat3::IDENTITY);
+        assert_eq!(unchecked.angle(), 0.0);
+    }
+
+    #[test]
+    fn test_polar_rotation_iterative() {
+        let r = Mat3::from_axis_angle(Vec3::new(0.3, -1.0, 2.0).normalize(), 0.9);
+        let perturbed = r + Mat3::from_cols(
+            Vec3::new(0.01, -0.02, 0.0),
+            Vec3::new(0.0, 0.015, 0.01),
+            Vec3::new(-0.01, 0.0, 0.02),
+        );
+
+        let polar = polar_rotation_iterative(&perturbed, 20);
+        assert!(is_rotation(&polar, 1e-5));
+        assert!(polar.abs_diff_eq(nearest_rotation(&perturbed), 1e-5));
+
+        // a rotation is a fixed point
+        assert!(polar_rotation_iterative(&r, 20).abs_diff_eq(r, 1e-6));
+
+        // a reflection falls back to the SVD
+        let reflected = perturbed * Mat3::from_diagonal(Vec3::new(1.0, 1.0, -1.0));
+        assert!(is_rotation(&polar_rotation_iterative(&reflected, 20), 1e-5));
+    }
+
+    #[test]
+    fn test_polar_rotation_iterative_scaled() {
+        let r = Mat3::from_axis_angle(Vec3::new(0.3, -1.0, 2.0).normalize(), 0.9);
+        let stretch = Mat3::from_diagonal(Vec3::new(1.2, 0.9, 1.0));
+        for scale in [1e-3, 1.0, 1000.0] {
+            for a in [r * scale, r * stretch * scale] {
+                let polar = polar_rotation_iterative(&a, 10);
+                assert!(is_rotation(&polar, 1e-5));
+                assert!(polar.abs_diff_eq(nearest_rotation(&a), 1e-5));
+            }
+        }
+    }
+
+    #[test]
+    fn test_procrustes_about_axis() {
+        let src = [
+            Vec3::new(1.0, 0.0, 0.5),
+            Vec3::new(-0.5, 2.0, -1.0),
+            Vec3::new(0.3, -1.2, 2.0),
+            Vec3::new(2.0, 1.0, 0.0),
+        ];
+        for angle in [0.0, 0.4, -1.3, 2.9] {
+            let r = Mat3::from_rotation_z(angle);
+            let dst = src.map(|p| r * p);
+            approx::assert_relative_eq!(
+                procrustes_about_axis(&src, &dst, Vec3::Z),
+                angle,
+                epsilon = 1e-5
+            );
+            // the axis direction fixes the sign, its length is irrelevant
+            approx::assert_relative_eq!(
+                procrustes_about_axis(&src, &dst, -2.0 * Vec3::Z),
+                -angle,
+                epsilon = 1e-5
+            );
+        }
+
+        // noise along the axis does not affect the angle
+        let r = Mat3::from_rotation_z(0.7);
+        let dst = src.map(|p| r * p + Vec3::new(0.0, 0.0, p.x));
+        approx::assert_relative_eq!(
+            procrustes_about_axis(&src, &dst, Vec3::Z),
+            0.7,
+            epsilon = 1e-5
+        );
+    }
+}
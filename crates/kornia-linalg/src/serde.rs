@@ -0,0 +1,130 @@
+use glam::Mat3;
+use serde::{Deserialize, Serialize};
+
+use crate::svd::SVD3Set;
+
+/// Human readable representation: named fields, singular values and row-major U and V.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "SVD3Set")]
+struct SVD3SetReadable {
+    singular_values: [f32; 3],
+    u: [[f32; 3]; 3],
+    v: [[f32; 3]; 3],
+}
+
+/// Compact representation: the singular values and the column-major entries of U and V.
+type SVD3SetCompact = ([f32; 3], [f32; 9], [f32; 9]);
+
+fn to_rows(m: &Mat3) -> [[f32; 3]; 3] {
+    m.transpose().to_cols_array_2d()
+}
+
+fn from_rows(rows: &[[f32; 3]; 3]) -> Mat3 {
+    Mat3::from_cols_array_2d(rows).transpose()
+}
+
+/// Serializes the decomposition in a format-dependent layout.
+///
+/// S is diagonal, so both layouts store its diagonal as the three singular values. Human
+/// readable formats (e.g. JSON) name them `singular_values` and store U and V as arrays of rows,
+/// so the output can be inspected by hand. Binary formats store the singular values followed by
+/// the column-major entries of U and V, 21 values in total.
+impl Serialize for SVD3Set {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            SVD3SetReadable {
+                singular_values: [self.s.x_axis.x, self.s.y_axis.y, self.s.z_axis.z],
+                u: to_rows(&self.u),
+                v: to_rows(&self.v),
+            }
+            .serialize(serializer)
+        } else {
+            let compact: SVD3SetCompact = (
+                [self.s.x_axis.x, self.s.y_axis.y, self.s.z_axis.z],
+                self.u.to_cols_array(),
+                self.v.to_cols_array(),
+            );
+            compact.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SVD3Set {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let SVD3SetReadable {
+                singular_values,
+                u,
+                v,
+            } = SVD3SetReadable::deserialize(deserializer)?;
+
+            Ok(SVD3Set {
+                u: from_rows(&u),
+                s: Mat3::from_diagonal(singular_values.into()),
+                v: from_rows(&v),
+            })
+        } else {
+            let (singular_values, u, v) = SVD3SetCompact::deserialize(deserializer)?;
+
+            Ok(SVD3Set {
+                u: Mat3::from_cols_array(&u),
+                s: Mat3::from_diagonal(singular_values.into()),
+                v: Mat3::from_cols_array(&v),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+    use crate::svd::svd3;
+
+    fn decomposition() -> SVD3Set {
+        svd3(&Mat3::from_cols(
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(-2.0, 4.0, 0.5),
+            Vec3::new(3.0, 1.0, 9.0),
+        ))
+    }
+
+    #[test]
+    fn test_serde_json() -> Result<(), Box<dyn std::error::Error>> {
+        let svd = decomposition();
+        let serialized = serde_json::to_string(&svd)?;
+
+        let value: serde_json::Value = serde_json::from_str(&serialized)?;
+        assert_eq!(value["singular_values"].as_array().map(Vec::len), Some(3));
+        let entry = |m: &str, i: usize, j: usize| value[m][i][j].as_f64().map(|x| x as f32);
+        assert_eq!(entry("u", 0, 1), Some(svd.u().y_axis.x));
+        assert_eq!(entry("v", 2, 0), Some(svd.v().x_axis.z));
+
+        let deserialized: SVD3Set = serde_json::from_str(&serialized)?;
+        assert_eq!(deserialized.u(), svd.u());
+        assert_eq!(deserialized.s(), svd.s());
+        assert_eq!(deserialized.v(), svd.v());
+        Ok(())
+    }
+
+    #[test]
+    fn test_serde_bincode() -> Result<(), Box<dyn std::error::Error>> {
+        let svd = decomposition();
+        let config = bincode::config::standard().with_fixed_int_encoding();
+        let encoded = bincode::serde::encode_to_vec(&svd, config)?;
+        assert_eq!(encoded.len(), 21 * std::mem::size_of::<f32>());
+
+        let (decoded, _): (SVD3Set, usize) = bincode::serde::decode_from_slice(&encoded, config)?;
+        assert_eq!(decoded.u(), svd.u());
+        assert_eq!(decoded.s(), svd.s());
+        assert_eq!(decoded.v(), svd.v());
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 // Reference: https://github.com/wi-re/tbtSVD/blob/master/source/SVD.h
-use glam::{Mat3, Quat};
+use glam::{Mat3, Quat, Vec3};
 use std::ops::{Index, IndexMut};
+
+use crate::mat3::{fold_entries, is_singular, mat3_is_finite, sym_is_finite};
 const GAMMA: f32 = 5.828_427_3;
 const CSTAR: f32 = 0.923_879_5;
 const SSTAR: f32 = 0.382_683_43;
@@ -13,6 +15,21 @@ fn fdiv(x: f32, y: f32) -> f32 {
     x / y
 }
 
+/// Batched version of the division used by the SVD, computing `out[i] = num[i] / den[i]`
+///
+/// Uses true IEEE division (never a reciprocal approximation) so the results match the scalar path exactly.
+///
+/// # Panics
+///
+/// Panics if the slices do not have the same length.
+pub fn fdiv_batch(num: &[f32], den: &[f32], out: &mut [f32]) {
+    assert_eq!(num.len(), den.len());
+    assert_eq!(num.len(), out.len());
+    for ((o, x), y) in out.iter_mut().zip(num).zip(den) {
+        *o = fdiv(*x, *y);
+    }
+}
+
 /// Calculates the reciprocal square root of x using a fast approximation.
 fn rsqrt(x: f32) -> f32 {
     let mut i: i32 = x.to_bits() as i32;
@@ -60,29 +77,49 @@ fn cond_neg_swap(c: bool, x: &mut f32, y: &mut f32) {
 
 #[derive(Debug, Clone)]
 /// A simple symmetric 3x3 Matrix class (contains no storage for (0, 1) (0, 2) and (1, 2)
-struct Symmetric3x3 {
+pub struct Symmetric3x3 {
     /// The element at row 0, column 0 of the matrix, typically the first diagonal element.
-    m_00: f32,
+    pub(crate) m_00: f32,
 
     /// The element at row 1, column 0 of the matrix. Since this is a symmetric matrix, it is equivalent to `m_01`.
-    m_10: f32,
+    pub(crate) m_10: f32,
 
     /// The element at row 1, column 1 of the matrix, the second diagonal element.
-    m_11: f32,
+    pub(crate) m_11: f32,
 
     /// The element at row 2, column 0 of the matrix. Since this is a symmetric matrix, it is equivalent to `m_02`.
-    m_20: f32,
+    pub(crate) m_20: f32,
 
     /// The element at row 2, column 1 of the matrix. Since this is a symmetric matrix, it is equivalent to `m_12`.
-    m_21: f32,
+    pub(crate) m_21: f32,
 
     /// The element at row 2, column 2 of the matrix, the third diagonal element.
-    m_22: f32,
+    pub(crate) m_22: f32,
 }
 
 impl Symmetric3x3 {
+    /// The 3x3 zero matrix
+    pub const ZERO: Self = Symmetric3x3 {
+        m_00: 0.0,
+        m_10: 0.0,
+        m_11: 0.0,
+        m_20: 0.0,
+        m_21: 0.0,
+        m_22: 0.0,
+    };
+
+    /// The 3x3 identity matrix
+    pub const IDENTITY: Self = Symmetric3x3 {
+        m_00: 1.0,
+        m_10: 0.0,
+        m_11: 1.0,
+        m_20: 0.0,
+        m_21: 0.0,
+        m_22: 1.0,
+    };
+
     /// Constructor from a regular Mat3x3 (assuming Mat3x3 exists)
-    fn from_mat3x3(mat: &Mat3) -> Self {
+    pub fn from_mat3x3(mat: &Mat3) -> Self {
         Symmetric3x3 {
             m_00: mat.x_axis.x,
             m_10: mat.y_axis.x,
@@ -92,6 +129,73 @@ impl Symmetric3x3 {
             m_22: mat.z_axis.z,
         }
     }
+
+    /// Constructor from the upper triangle stored in row-major order `[m00, m01, m02, m11, m12, m22]`
+    pub fn from_upper_triangular(data: &[f32; 6]) -> Self {
+        Symmetric3x3 {
+            m_00: data[0],
+            m_10: data[1],
+            m_20: data[2],
+            m_11: data[3],
+            m_21: data[4],
+            m_22: data[5],
+        }
+    }
+
+    /// Returns the upper triangle in row-major order `[m00, m01, m02, m11, m12, m22]`
+    pub fn to_upper_triangular(&self) -> [f32; 6] {
+        [
+            self.m_00, self.m_10, self.m_20, self.m_11, self.m_21, self.m_22,
+        ]
+    }
+
+    /// Computes the congruence transform A * S * A^T directly in the symmetric representation
+    ///
+    /// Only the six independent entries are evaluated, so the result is exactly symmetric.
+    pub fn congruence_transform(&self, a: &Mat3) -> Symmetric3x3 {
+        let s = self.to_mat3x3();
+        let (r0, r1, r2) = (a.row(0), a.row(1), a.row(2));
+        let (s0, s1) = (s.mul_vec3(r0), s.mul_vec3(r1));
+        Symmetric3x3 {
+            m_00: r0.dot(s0),
+            m_10: r1.dot(s0),
+            m_11: r1.dot(s1),
+            m_20: r2.dot(s0),
+            m_21: r2.dot(s1),
+            m_22: r2.dot(s.mul_vec3(r2)),
+        }
+    }
+
+    /// Computes the inverse congruence transform A^-1 * S * A^-T
+    ///
+    /// Returns `None` if A is singular, checked relative to the norms of its columns.
+    pub fn inverse_congruence(&self, a: &Mat3) -> Option<Symmetric3x3> {
+        if is_singular(a) {
+            return None;
+        }
+        Some(self.congruence_transform(&a.inverse()))
+    }
+
+    /// Sum of the diagonal elements
+    pub fn trace(&self) -> f32 {
+        self.m_00 + self.m_11 + self.m_22
+    }
+
+    /// Determinant of the matrix
+    pub fn determinant(&self) -> f32 {
+        self.m_00 * (self.m_11 * self.m_22 - self.m_21 * self.m_21)
+            - self.m_10 * (self.m_10 * self.m_22 - self.m_21 * self.m_20)
+            + self.m_20 * (self.m_10 * self.m_21 - self.m_11 * self.m_20)
+    }
+
+    /// Expands the symmetric matrix into a full Mat3x3
+    pub fn to_mat3x3(&self) -> Mat3 {
+        Mat3::from_cols(
+            Vec3::new(self.m_00, self.m_10, self.m_20),
+            Vec3::new(self.m_10, self.m_11, self.m_21),
+            Vec3::new(self.m_20, self.m_21, self.m_22),
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -118,13 +222,13 @@ struct QR3 {
 /// Helper struct to store 3 Matrices to avoid OUT parameters on functions
 pub struct SVD3Set {
     /// The matrix of left singular vectors.
-    u: Mat3,
+    pub(crate) u: Mat3,
 
     /// The diagonal matrix of singular values.
-    s: Mat3,
+    pub(crate) s: Mat3,
 
     /// The matrix of right singular vectors.
-    v: Mat3,
+    pub(crate) v: Mat3,
 }
 
 impl SVD3Set {
@@ -182,16 +286,25 @@ fn approximate_givens_quaternion(a: &Symmetric3x3) -> Givens {
 /// This struct provides custom indexing behavior for quaternion components (`x`, `y`, `z`, and `w`),
 /// enabling access and mutation using an index (e.g., `q[0]`, `q[1]`, etc.). It implements both
 /// the `Index` and `IndexMut` traits to allow for immutable and mutable access to the quaternion's components.
-struct IndexedQuat(Quat);
+pub struct IndexedQuat(Quat);
 
 impl IndexedQuat {
-    fn new(q: Quat) -> Self {
+    /// Wraps a quaternion to allow indexing into its components.
+    pub fn new(q: Quat) -> Self {
         IndexedQuat(q)
     }
 
-    fn to_quat(&self) -> Quat {
+    /// Get the wrapped quaternion.
+    pub fn to_quat(&self) -> Quat {
         self.0
     }
+
+    /// Checks whether both quaternions represent the same rotation, up to `tol` per component
+    ///
+    /// Since `q` and `-q` encode the same rotation (double cover), both signs of `other` are tried.
+    pub fn approx_eq_rotation(&self, other: &IndexedQuat, tol: f32) -> bool {
+        self.0.abs_diff_eq(other.0, tol) || self.0.abs_diff_eq(-other.0, tol)
+    }
 }
 
 impl Index<usize> for IndexedQuat {
@@ -274,6 +387,10 @@ fn jacobi_conjugation(x: usize, y: usize, z: usize, s: &mut Symmetric3x3, q: &mu
 /// Function used to contain the givens permutations and the loop of the jacobi steps controlled by JACOBI_STEPS
 /// Returns the quaternion q containing the cumultative result used to reconstruct S
 fn jacobi_eigenanalysis(mut s: Symmetric3x3) -> Mat3 {
+    debug_assert!(
+        sym_is_finite(&s),
+        "non-finite input to the eigenanalysis: {s:?}"
+    );
     let mut q = IndexedQuat::new(Quat::from_xyzw(0.0, 0.0, 0.0, 1.0));
     for _i in 0..JACOBI_STEPS {
         jacobi_conjugation(0, 1, 2, &mut s, &mut q);
@@ -281,16 +398,109 @@ fn jacobi_eigenanalysis(mut s: Symmetric3x3) -> Mat3 {
         jacobi_conjugation(2, 0, 1, &mut s, &mut q);
     }
 
-    Mat3::from_quat(q.to_quat())
+    // The approximate Givens angles let the quaternion drift from unit length
+    Mat3::from_quat(q.to_quat().normalize())
+}
+
+/// Computes the eigendecomposition of a symmetric 3x3 matrix using the Jacobi fast path of the SVD
+///
+/// Returns the eigenvalues sorted in descending order and the matching eigenvectors as the columns of the matrix
+pub fn symmetric_eigen(s: &Symmetric3x3) -> (Vec3, Mat3) {
+    let v = jacobi_eigenanalysis(s.clone());
+    let d = v.transpose().mul_mat3(&s.to_mat3x3()).mul_mat3(&v);
+
+    let mut pairs = [
+        (d.x_axis.x, v.x_axis),
+        (d.y_axis.y, v.y_axis),
+        (d.z_axis.z, v.z_axis),
+    ];
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    (
+        Vec3::new(pairs[0].0, pairs[1].0, pairs[2].0),
+        Mat3::from_cols(pairs[0].1, pairs[1].1, pairs[2].1),
+    )
+}
+
+/// Helper function used to swap the column indices X and Y if c == true
+fn cond_swap_index(c: bool, x: &mut usize, y: &mut usize) {
+    if c {
+        std::mem::swap(x, y);
+    }
+}
+
+/// Returns true if the column (rho_a, idx_a) must be placed after the column (rho_b, idx_b)
+///
+/// Columns are sorted by descending squared norm, and exactly equal norms keep the original
+/// column order so the result does not depend on how the comparisons are evaluated.
+fn sorts_after(rho_a: f32, idx_a: usize, rho_b: f32, idx_b: usize) -> bool {
+    rho_a < rho_b || (rho_a == rho_b && idx_a > idx_b)
+}
+
+/// Applies a scalar function to a symmetric matrix through its eigendecomposition, V * f(D) * V^T
+pub fn apply_eigen<F: Fn(f32) -> f32>(s: &Symmetric3x3, f: F) -> Symmetric3x3 {
+    let (values, vectors) = symmetric_eigen(s);
+    let m = vectors
+        .mul_mat3(&Mat3::from_diagonal(values.map(f)))
+        .mul_mat3(&vectors.transpose());
+    Symmetric3x3::from_mat3x3(&((m + m.transpose()) * 0.5))
+}
+
+/// Minimizes the quadratic form x^T * S * x over unit vectors
+///
+/// Returns the minimum value, the smallest eigenvalue, and the unit vector achieving it.
+pub fn min_on_sphere(s: &Symmetric3x3) -> (f32, Vec3) {
+    let (values, vectors) = symmetric_eigen(s);
+    (values.z, vectors.z_axis)
+}
+
+/// Maximizes the quadratic form x^T * S * x over unit vectors
+///
+/// Returns the maximum value, the largest eigenvalue, and the unit vector achieving it.
+pub fn max_on_sphere(s: &Symmetric3x3) -> (f32, Vec3) {
+    let (values, vectors) = symmetric_eigen(s);
+    (values.x, vectors.x_axis)
+}
+
+/// Refines an approximate eigenvector with one step of inverse iteration
+///
+/// Solves `(S - lambda * I) * w = v` and returns `w` normalized, with the same orientation as `v`.
+/// Components along the other eigenvectors shrink by the ratio of the eigenvalue distances, so
+/// a good `lambda` (e.g. from [`symmetric_eigen`] or the Rayleigh quotient) converges fast.
+/// The system is solved with the adjugate, which has the direction of the inverse without
+/// dividing by the determinant, so an exact `lambda` does not break the step. If `v` is mapped
+/// to zero (e.g. S = lambda * I) it is returned normalized.
+pub fn refine_eigenvector(s: &Symmetric3x3, lambda: f32, v: Vec3) -> Vec3 {
+    let shifted = s.to_mat3x3() - Mat3::from_diagonal(Vec3::splat(lambda));
+    let (c0, c1, c2) = (shifted.x_axis, shifted.y_axis, shifted.z_axis);
+    let adjugate = Mat3::from_cols(c1.cross(c2), c2.cross(c0), c0.cross(c1)).transpose();
+
+    match adjugate.mul_vec3(v).try_normalize() {
+        Some(w) if w.dot(v) < 0.0 => -w,
+        Some(w) => w,
+        None => v.normalize_or_zero(),
+    }
+}
+
+/// Computes the smallest absolute difference between consecutive sorted eigenvalues
+///
+/// A small gap means the matching eigenvectors are poorly determined, so callers should not
+/// trust the principal axes spanned by nearly equal eigenvalues.
+pub fn eigen_gap(s: &Symmetric3x3) -> f32 {
+    let (values, _) = symmetric_eigen(s);
+    f32::min(values.x - values.y, values.y - values.z)
 }
 
 /// Implementation of Algorithm 3
-fn sort_singular_values(b: &mut Mat3, v: &mut Mat3) {
+///
+/// Returns the permutation applied to the columns: entry i is the original index of the column now at position i
+fn sort_singular_values(b: &mut Mat3, v: &mut Mat3) -> [usize; 3] {
     let mut rho1 = dist2(b.x_axis.x, b.x_axis.y, b.x_axis.z);
     let mut rho2 = dist2(b.y_axis.x, b.y_axis.y, b.y_axis.z);
     let mut rho3 = dist2(b.z_axis.x, b.z_axis.y, b.z_axis.z);
+    let (mut idx1, mut idx2, mut idx3) = (0, 1, 2);
 
-    let mut c = rho1 < rho2;
+    let mut c = sorts_after(rho1, idx1, rho2, idx2);
     cond_neg_swap(c, &mut b.x_axis.x, &mut b.y_axis.x);
     cond_neg_swap(c, &mut v.x_axis.x, &mut v.y_axis.x);
     cond_neg_swap(c, &mut b.x_axis.y, &mut b.y_axis.y);
@@ -298,8 +508,9 @@ fn sort_singular_values(b: &mut Mat3, v: &mut Mat3) {
     cond_neg_swap(c, &mut b.x_axis.z, &mut b.y_axis.z);
     cond_neg_swap(c, &mut v.x_axis.z, &mut v.y_axis.z);
     cond_swap(c, &mut rho1, &mut rho2);
+    cond_swap_index(c, &mut idx1, &mut idx2);
 
-    c = rho1 < rho3;
+    c = sorts_after(rho1, idx1, rho3, idx3);
     cond_neg_swap(c, &mut b.x_axis.x, &mut b.z_axis.x);
     cond_neg_swap(c, &mut v.x_axis.x, &mut v.z_axis.x);
     cond_neg_swap(c, &mut b.x_axis.y, &mut b.z_axis.y);
@@ -307,14 +518,18 @@ fn sort_singular_values(b: &mut Mat3, v: &mut Mat3) {
     cond_neg_swap(c, &mut b.x_axis.z, &mut b.z_axis.z);
     cond_neg_swap(c, &mut v.x_axis.z, &mut v.z_axis.z);
     cond_swap(c, &mut rho1, &mut rho3);
+    cond_swap_index(c, &mut idx1, &mut idx3);
 
-    c = rho2 < rho3;
+    c = sorts_after(rho2, idx2, rho3, idx3);
     cond_neg_swap(c, &mut b.y_axis.x, &mut b.z_axis.x);
     cond_neg_swap(c, &mut v.y_axis.x, &mut v.z_axis.x);
     cond_neg_swap(c, &mut b.y_axis.y, &mut b.z_axis.y);
     cond_neg_swap(c, &mut v.y_axis.y, &mut v.z_axis.y);
     cond_neg_swap(c, &mut b.y_axis.z, &mut b.z_axis.z);
     cond_neg_swap(c, &mut v.y_axis.z, &mut v.z_axis.z);
+    cond_swap_index(c, &mut idx2, &mut idx3);
+
+    [idx1, idx2, idx3]
 }
 
 /// Implementation of Algorithm 4
@@ -330,7 +545,8 @@ fn qr_givens_quaternion(a1: f32, a2: f32) -> Givens {
     let b = a1 < 0.0;
     cond_swap(b, &mut g.sh, &mut g.ch);
 
-    let w = rsqrt(g.ch * g.ch + g.sh * g.sh);
+    // Q is assembled assuming unit quaternions, so use the higher precision rsqrt
+    let w = rsqrt1(g.ch * g.ch + g.sh * g.sh);
     g.ch *= w;
     g.sh *= w;
     g
@@ -338,6 +554,10 @@ fn qr_givens_quaternion(a1: f32, a2: f32) -> Givens {
 
 /// Implements a QR decomposition of a Matrix
 fn qr_decomposition(b_mat: &mut Mat3) -> QR3 {
+    debug_assert!(
+        mat3_is_finite(b_mat),
+        "non-finite input to the QR: {b_mat:?}"
+    );
     let mut q = Mat3::ZERO;
     let mut r = Mat3::ZERO;
 
@@ -389,52 +609,161 @@ fn qr_decomposition(b_mat: &mut Mat3) -> QR3 {
     r.y_axis.z = -b * b_mat.y_axis.y + a * b_mat.y_axis.z;
     r.z_axis.z = -b * b_mat.z_axis.y + a * b_mat.z_axis.z;
 
-    // Construct the cumulative rotation Q = Q1 * Q2 * Q3
+    // Construct the cumulative rotation Q = Q1 * Q2 * Q3 (glam matrices are column-major)
     let sh12 = 2.0 * (g1.sh * g1.sh - 0.5);
     let sh22 = 2.0 * (g2.sh * g2.sh - 0.5);
     let sh32 = 2.0 * (g3.sh * g3.sh - 0.5);
 
     q.x_axis.x = sh12 * sh22;
-    q.x_axis.y = 4.0 * g2.ch * g3.ch * sh12 * g2.sh * g3.sh + 2.0 * g1.ch * g1.sh * sh32;
-    q.x_axis.z = 4.0 * g1.ch * g3.ch * g1.sh * g3.sh - 2.0 * g2.ch * sh12 * g2.sh * sh32;
+    q.y_axis.x = 4.0 * g2.ch * g3.ch * sh12 * g2.sh * g3.sh + 2.0 * g1.ch * g1.sh * sh32;
+    q.z_axis.x = 4.0 * g1.ch * g3.ch * g1.sh * g3.sh - 2.0 * g2.ch * sh12 * g2.sh * sh32;
 
-    q.y_axis.x = -2.0 * g1.ch * g1.sh * sh22;
+    q.x_axis.y = -2.0 * g1.ch * g1.sh * sh22;
     q.y_axis.y = -8.0 * g1.ch * g2.ch * g3.ch * g1.sh * g2.sh * g3.sh + sh12 * sh32;
-    q.y_axis.z =
+    q.z_axis.y =
         -2.0 * g3.ch * g3.sh + 4.0 * g1.sh * (g3.ch * g1.sh * g3.sh + g1.ch * g2.ch * g2.sh * sh32);
 
-    q.z_axis.x = 2.0 * g2.ch * g2.sh;
-    q.z_axis.y = -2.0 * g3.ch * sh22 * g3.sh;
+    q.x_axis.z = 2.0 * g2.ch * g2.sh;
+    q.y_axis.z = -2.0 * g3.ch * sh22 * g3.sh;
     q.z_axis.z = sh22 * sh32;
 
     QR3 { q, r }
 }
 
+/// Computes the Gram matrix A^T * A of a 3x3 matrix
+///
+/// The entries are squared, so they overflow for entries of A beyond about `1.8e19`
+/// (the square root of `f32::MAX`). [`svd3`] scales A before forming its Gram matrix.
+pub fn ata(a: &Mat3) -> Symmetric3x3 {
+    Symmetric3x3::from_mat3x3(&(a.transpose().mul_mat3(a)))
+}
+
+#[derive(Debug, Clone)]
+/// Incrementally accumulates the Gram matrix A^T * A of a design matrix whose rows arrive over time
+///
+/// The singular values of the accumulated design matrix can be queried at any time to monitor
+/// its rank and conditioning. The rows are accumulated relative to the largest absolute entry
+/// seen so far, so the squared entries do not overflow for large rows.
+pub struct GramAccumulator {
+    /// The accumulated Gram matrix, divided by the square of `scale`.
+    gram: Symmetric3x3,
+
+    /// The largest absolute entry of the rows added so far.
+    scale: f32,
+
+    /// The number of rows added so far.
+    rows: usize,
+}
+
+impl Default for GramAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GramAccumulator {
+    /// Creates an empty accumulator
+    pub fn new() -> Self {
+        GramAccumulator {
+            gram: Symmetric3x3::ZERO,
+            scale: 0.0,
+            rows: 0,
+        }
+    }
+
+    /// Adds a row of the design matrix, accumulating its outer product
+    pub fn add_row(&mut self, row: Vec3) {
+        self.rows += 1;
+
+        let max = row.abs().max_element();
+        if max > self.scale {
+            let ratio = self.scale / max;
+            let rescaled = self.gram.to_upper_triangular().map(|x| x * ratio * ratio);
+            self.gram = Symmetric3x3::from_upper_triangular(&rescaled);
+            self.scale = max;
+        }
+        if self.scale == 0.0 {
+            return;
+        }
+
+        let row = row / self.scale;
+        self.gram.m_00 += row.x * row.x;
+        self.gram.m_10 += row.y * row.x;
+        self.gram.m_11 += row.y * row.y;
+        self.gram.m_20 += row.z * row.x;
+        self.gram.m_21 += row.z * row.y;
+        self.gram.m_22 += row.z * row.z;
+    }
+
+    /// Computes the accumulated Gram matrix
+    ///
+    /// Unlike [`GramAccumulator::svd_values`], this overflows for rows with entries beyond about
+    /// `1.8e19`, since the entries of the Gram matrix are squared.
+    pub fn gram(&self) -> Symmetric3x3 {
+        let scale2 = self.scale * self.scale;
+        Symmetric3x3::from_upper_triangular(&self.gram.to_upper_triangular().map(|x| x * scale2))
+    }
+
+    /// Get the number of rows added so far.
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Singular values of the accumulated design matrix in descending order
+    ///
+    /// Computed as the square roots of the eigenvalues of the Gram matrix using the symmetric fast path.
+    pub fn svd_values(&self) -> Vec3 {
+        let (values, _) = symmetric_eigen(&self.gram);
+        values.max(Vec3::ZERO).map(f32::sqrt) * self.scale
+    }
+}
+
 /// Wrapping function used to contain all of the required sub calls
 pub fn svd3(a: &Mat3) -> SVD3Set {
+    svd_with_permutation(a).0
+}
+
+/// Computes the SVD and also returns the permutation used to sort the singular values
+///
+/// Entry i of the permutation is the position, in the unsorted order produced by the Jacobi
+/// eigenanalysis, of the singular value found at position i of the output. This lets callers
+/// reorder external per-axis data consistently with the decomposition.
+pub fn svd_with_permutation(a: &Mat3) -> (SVD3Set, [usize; 3]) {
+    debug_assert!(mat3_is_finite(a), "non-finite input to the SVD: {a:?}");
+
+    // The SVD is scale-equivariant, so work on A scaled by its largest absolute entry to keep
+    // A^T * A from overflowing or underflowing
+    let scale = fold_entries(&a.abs(), 0.0, f32::max);
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    let a = &(*a / scale);
+
     // Compute the eigenvectors of A^T * A, which is V in SVD (Singular Vectors)
-    let v = jacobi_eigenanalysis(Symmetric3x3::from_mat3x3(&(a.transpose().mul_mat3(a))));
+    let mut v = jacobi_eigenanalysis(ata(a));
     // Compute B = A * V
     let mut b = a.mul_mat3(&v);
 
-    // Sort the singular values
-    sort_singular_values(&mut b, &mut v.clone());
+    // Sort the singular values, applying the same permutation to V
+    let permutation = sort_singular_values(&mut b, &mut v);
 
     // Perform QR decomposition on B to get Q and R
     let qr = qr_decomposition(&mut b);
 
-    // Return the SVD result, which includes Q (as U), R (as S), and V
-    SVD3Set {
-        u: qr.q,
-        s: qr.r,
-        v,
-    }
+    // Return the SVD result, which includes Q (as U), the diagonal of R (as S), and V. The
+    // off-diagonal entries of R only hold roundoff residue from the Jacobi sweeps
+    let r = qr.r * scale;
+    (
+        SVD3Set {
+            u: qr.q,
+            s: Mat3::from_diagonal(Vec3::new(r.x_axis.x, r.y_axis.y, r.z_axis.z)),
+            v,
+        },
+        permutation,
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use glam::Vec3;
-
     use super::*;
 
     #[test]
@@ -448,12 +777,12 @@ mod tests {
 
         // Perform SVD on matrix A
         let svd_result = svd3(&a);
-        let _ = a.abs_diff_eq(
+        assert!(a.abs_diff_eq(
             svd_result
                 .u
                 .mul_mat3(&(svd_result.s.mul_mat3(&svd_result.v.transpose()))),
-            SVD3_EPSILON,
-        );
+            1e-4,
+        ));
     }
 
     #[test]
@@ -467,12 +796,12 @@ mod tests {
 
         // Perform SVD on matrix A
         let svd_result = svd3(&a);
-        let _ = a.abs_diff_eq(
+        assert!(a.abs_diff_eq(
             svd_result
                 .u
                 .mul_mat3(&(svd_result.s.mul_mat3(&svd_result.v.transpose()))),
-            SVD3_EPSILON,
-        );
+            1e-4,
+        ));
     }
 
     #[test]
@@ -486,12 +815,12 @@ mod tests {
 
         // Perform SVD on matrix A
         let svd_result = svd3(&a);
-        let _ = a.abs_diff_eq(
+        assert!(a.abs_diff_eq(
             svd_result
                 .u
                 .mul_mat3(&(svd_result.s.mul_mat3(&svd_result.v.transpose()))),
-            SVD3_EPSILON,
-        );
+            1e-4,
+        ));
     }
 
     #[test]
@@ -505,11 +834,344 @@ mod tests {
 
         // Perform SVD on matrix A
         let svd_result = svd3(&a);
-        let _ = a.abs_diff_eq(
+        assert!(a.abs_diff_eq(
             svd_result
                 .u
                 .mul_mat3(&(svd_result.s.mul_mat3(&svd_result.v.transpose()))),
-            SVD3_EPSILON,
+            1e-4,
+        ));
+    }
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let a = Mat3 {
+            x_axis: Vec3::new(4.0, 1.0, 0.5),
+            y_axis: Vec3::new(1.0, 3.0, -0.25),
+            z_axis: Vec3::new(0.5, -0.25, 1.0),
+        };
+
+        let (values, vectors) = symmetric_eigen(&Symmetric3x3::from_mat3x3(&a));
+        assert!(values.x >= values.y && values.y >= values.z);
+        for i in 0..3 {
+            let v = vectors.col(i);
+            assert!((a * v).abs_diff_eq(values[i] * v, 1e-4));
+        }
+        assert!((vectors.transpose() * vectors).abs_diff_eq(Mat3::IDENTITY, 1e-5));
+    }
+
+    #[test]
+    fn test_fdiv_batch() {
+        let num = [1.0, -3.0, 7.5, 0.1, 1e-30, 2.0];
+        let den = [3.0, 7.0, 0.3, 0.7, 3e10, 0.0];
+        let mut out = [0.0; 6];
+
+        fdiv_batch(&num, &den, &mut out);
+        for i in 0..num.len() {
+            assert_eq!(out[i].to_bits(), (num[i] / den[i]).to_bits());
+        }
+    }
+
+    #[test]
+    fn test_svd3_general() {
+        let a = Mat3 {
+            x_axis: Vec3::new(1.0, 2.0, 3.0),
+            y_axis: Vec3::new(-2.0, 4.0, 0.5),
+            z_axis: Vec3::new(3.0, 1.0, 9.0),
+        };
+
+        let svd_result = svd3(&a);
+        let (u, s, v) = (svd_result.u(), svd_result.s(), svd_result.v());
+        assert!(a.abs_diff_eq(u.mul_mat3(&s.mul_mat3(&v.transpose())), 1e-4));
+        assert!(u.transpose().mul_mat3(u).abs_diff_eq(Mat3::IDENTITY, 1e-5));
+        assert!(v.transpose().mul_mat3(v).abs_diff_eq(Mat3::IDENTITY, 1e-5));
+        assert!(s.x_axis.x >= s.y_axis.y && s.y_axis.y >= s.z_axis.z.abs());
+        assert_eq!(
+            *s,
+            Mat3::from_diagonal(Vec3::new(s.x_axis.x, s.y_axis.y, s.z_axis.z))
         );
     }
+
+    #[test]
+    fn test_gram_accumulator() {
+        let a = Mat3 {
+            x_axis: Vec3::new(1.0, 2.0, 3.0),
+            y_axis: Vec3::new(-2.0, 4.0, 0.5),
+            z_axis: Vec3::new(3.0, 1.0, 9.0),
+        };
+        let b = Mat3::from_rotation_y(0.4) * 2.0;
+
+        let mut acc = GramAccumulator::new();
+        for i in 0..3 {
+            acc.add_row(a.row(i));
+        }
+        assert_eq!(acc.rows(), 3);
+        assert!(acc
+            .gram()
+            .to_mat3x3()
+            .abs_diff_eq(ata(&a).to_mat3x3(), 1e-5));
+
+        let s = svd3(&a);
+        let expected = Vec3::new(s.s().x_axis.x, s.s().y_axis.y, s.s().z_axis.z).abs();
+        assert!(acc.svd_values().abs_diff_eq(expected, 1e-3));
+
+        // stacking a second block of rows adds its Gram matrix
+        for i in 0..3 {
+            acc.add_row(b.row(i));
+        }
+        let batch = ata(&a).to_mat3x3() + ata(&b).to_mat3x3();
+        assert!(acc.gram().to_mat3x3().abs_diff_eq(batch, 1e-4));
+
+        let (values, _) = symmetric_eigen(&Symmetric3x3::from_mat3x3(&batch));
+        assert!(acc.svd_values().abs_diff_eq(values.map(f32::sqrt), 1e-4));
+
+        // rows whose squared entries overflow f32
+        let mut acc = GramAccumulator::new();
+        acc.add_row(Vec3::new(1e20, 0.0, 0.0));
+        acc.add_row(Vec3::new(0.0, 3e19, 4e19));
+        let values = acc.svd_values();
+        approx::assert_relative_eq!(values.x, 1e20, max_relative = 1e-5);
+        approx::assert_relative_eq!(values.y, 5e19, max_relative = 1e-5);
+        assert!(values.z.abs() <= 1e-6 * values.x);
+    }
+
+    #[test]
+    fn test_sort_singular_values_ties() {
+        // columns 1 and 2 have exactly equal norms and must keep their original order
+        let mut b = Mat3::from_diagonal(Vec3::new(1.0, 3.0, 3.0));
+        let mut v = Mat3::IDENTITY;
+        assert_eq!(sort_singular_values(&mut b, &mut v), [1, 2, 0]);
+        assert_eq!(b, Mat3::from_cols(3.0 * Vec3::Y, 3.0 * Vec3::Z, Vec3::X));
+        assert_eq!(v, Mat3::from_cols(Vec3::Y, Vec3::Z, Vec3::X));
+
+        let mut b = Mat3::from_cols(Vec3::new(0.0, 2.0, 0.0), Vec3::X, Vec3::new(2.0, 0.0, 0.0));
+        let mut v = Mat3::IDENTITY;
+        assert_eq!(sort_singular_values(&mut b, &mut v), [0, 2, 1]);
+        assert_eq!(
+            b,
+            Mat3::from_cols(Vec3::new(0.0, 2.0, 0.0), Vec3::new(2.0, 0.0, 0.0), -Vec3::X)
+        );
+        assert_eq!(v, Mat3::from_cols(Vec3::X, Vec3::Z, -Vec3::Y));
+
+        // all norms equal: nothing moves
+        let a = Mat3::from_cols(
+            Vec3::new(1.0, 2.0, 2.0),
+            Vec3::new(2.0, 1.0, 2.0),
+            Vec3::new(2.0, 2.0, 1.0),
+        );
+        let mut b = a;
+        let mut v = Mat3::IDENTITY;
+        assert_eq!(sort_singular_values(&mut b, &mut v), [0, 1, 2]);
+        assert_eq!(b, a);
+        assert_eq!(v, Mat3::IDENTITY);
+    }
+
+    #[test]
+    fn test_eigen_gap() {
+        let r = Mat3::from_axis_angle(Vec3::new(1.0, -1.0, 2.0).normalize(), 0.6);
+        let rotated =
+            |d: Vec3| Symmetric3x3::from_mat3x3(&(r * Mat3::from_diagonal(d) * r.transpose()));
+
+        approx::assert_relative_eq!(
+            eigen_gap(&rotated(Vec3::new(10.0, 5.0, 1.0))),
+            4.0,
+            epsilon = 1e-4
+        );
+        approx::assert_relative_eq!(
+            eigen_gap(&rotated(Vec3::new(3.0, 1.0, 3.01))),
+            0.01,
+            epsilon = 1e-4
+        );
+        assert!(eigen_gap(&Symmetric3x3::IDENTITY) < 1e-6);
+    }
+
+    #[test]
+    fn test_indexed_quat_approx_eq_rotation() {
+        let q = IndexedQuat::new(Quat::from_axis_angle(
+            Vec3::new(1.0, 2.0, 3.0).normalize(),
+            0.7,
+        ));
+        let neg = IndexedQuat::new(-q.to_quat());
+        assert!(q.approx_eq_rotation(&neg, 1e-6));
+        assert!(neg.approx_eq_rotation(&q, 1e-6));
+        assert!(q.approx_eq_rotation(&q, 0.0));
+
+        let other = IndexedQuat::new(Quat::from_axis_angle(
+            Vec3::new(1.0, 2.0, 3.0).normalize(),
+            0.8,
+        ));
+        assert!(!q.approx_eq_rotation(&other, 1e-3));
+        assert!(q.approx_eq_rotation(&other, 0.1));
+    }
+
+    #[test]
+    fn test_svd_with_permutation() {
+        let a = Mat3 {
+            x_axis: Vec3::new(0.5, 0.0, 0.2),
+            y_axis: Vec3::new(-0.1, 3.0, 0.0),
+            z_axis: Vec3::new(0.0, 0.4, 7.0),
+        };
+
+        // unsorted singular values, as produced by the eigenanalysis before sorting
+        let b = a.mul_mat3(&jacobi_eigenanalysis(ata(&a)));
+        let unsorted = [b.x_axis.length(), b.y_axis.length(), b.z_axis.length()];
+
+        let (svd_result, permutation) = svd_with_permutation(&a);
+        let s = svd_result.s();
+        let sorted = [s.x_axis.x, s.y_axis.y, s.z_axis.z.abs()];
+        for i in 0..3 {
+            approx::assert_relative_eq!(unsorted[permutation[i]], sorted[i], epsilon = 1e-4);
+        }
+
+        let mut seen = permutation;
+        seen.sort();
+        assert_eq!(seen, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_symmetric_upper_triangular() {
+        let data = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let s = Symmetric3x3::from_upper_triangular(&data);
+        assert_eq!(s.to_upper_triangular(), data);
+
+        // row-major [[1, 2, 3], [2, 4, 5], [3, 5, 6]]
+        let m = Mat3::from_cols(
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(2.0, 4.0, 5.0),
+            Vec3::new(3.0, 5.0, 6.0),
+        );
+        assert_eq!(s.to_mat3x3(), m);
+        assert_eq!(
+            Symmetric3x3::from_mat3x3(&m).to_upper_triangular(),
+            s.to_upper_triangular()
+        );
+    }
+
+    #[test]
+    fn test_svd3_large_and_small_magnitude() {
+        for scale in [1e20, 1e-20] {
+            let a = Mat3::from_diagonal(Vec3::new(scale, 1.0, 1.0));
+            let svd_result = svd3(&a);
+            let s = svd_result.s();
+            approx::assert_relative_eq!(s.x_axis.x.max(s.z_axis.z.abs()), scale.max(1.0));
+            let reconstructed = svd_result
+                .u()
+                .mul_mat3(&s.mul_mat3(&svd_result.v().transpose()));
+            assert!((reconstructed / scale.max(1.0)).abs_diff_eq(a / scale.max(1.0), 1e-5));
+        }
+
+        let a = Mat3::from_cols(
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(-2.0, 4.0, 0.5),
+            Vec3::new(3.0, 1.0, 9.0),
+        );
+        let (expected, large) = (svd3(&a), svd3(&(a * 1e25)));
+        assert!((*large.s() / 1e25).abs_diff_eq(*expected.s(), 1e-4));
+        assert!(large.u().abs_diff_eq(*expected.u(), 1e-5));
+        assert!(large.v().abs_diff_eq(*expected.v(), 1e-5));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "non-finite input to the SVD")]
+    fn test_svd3_non_finite() {
+        let mut a = Mat3::IDENTITY;
+        a.y_axis.x = f32::NAN;
+        svd3(&a);
+    }
+
+    #[test]
+    fn test_apply_eigen() {
+        let a = Mat3 {
+            x_axis: Vec3::new(4.0, 1.0, 0.5),
+            y_axis: Vec3::new(1.0, 3.0, -0.25),
+            z_axis: Vec3::new(0.5, -0.25, 1.0),
+        };
+        let s = Symmetric3x3::from_mat3x3(&a);
+        assert!(apply_eigen(&s, |x| x).to_mat3x3().abs_diff_eq(a, 1e-5));
+
+        let sqrt = apply_eigen(&s, f32::sqrt).to_mat3x3();
+        assert!((sqrt * sqrt).abs_diff_eq(a, 1e-4));
+    }
+
+    #[test]
+    fn test_congruence_transform() {
+        let s = Symmetric3x3::from_upper_triangular(&[4.0, 1.0, 0.5, 3.0, -0.25, 1.0]);
+        let a = Mat3 {
+            x_axis: Vec3::new(1.0, 2.0, 3.0),
+            y_axis: Vec3::new(-2.0, 4.0, 0.5),
+            z_axis: Vec3::new(3.0, 1.0, 9.0),
+        };
+
+        let dense = a * s.to_mat3x3() * a.transpose();
+        assert!(s
+            .congruence_transform(&a)
+            .to_mat3x3()
+            .abs_diff_eq(dense, 1e-3));
+
+        let inv = a.inverse();
+        let dense_inv = inv * s.to_mat3x3() * inv.transpose();
+        let out = s.inverse_congruence(&a).unwrap();
+        assert!(out.to_mat3x3().abs_diff_eq(dense_inv, 1e-5));
+        assert!(out
+            .congruence_transform(&a)
+            .to_mat3x3()
+            .abs_diff_eq(s.to_mat3x3(), 1e-4));
+
+        let singular = Mat3::from_cols(Vec3::X, Vec3::Y, Vec3::new(1.0, 1.0, 0.0));
+        assert!(s.inverse_congruence(&singular).is_none());
+
+        // well-conditioned despite the spread of the diagonal
+        let scale = Mat3::from_diagonal(Vec3::new(1e4, 1.0, 1.0));
+        let out = s.inverse_congruence(&scale).unwrap();
+        assert!(out
+            .congruence_transform(&scale)
+            .to_mat3x3()
+            .abs_diff_eq(s.to_mat3x3(), 1e-4));
+    }
+
+    #[test]
+    fn test_extrema_on_sphere() {
+        let s = Symmetric3x3::from_upper_triangular(&[4.0, 1.0, 0.5, 3.0, -0.25, 1.0]);
+        let (values, vectors) = symmetric_eigen(&s);
+        let quadratic = |x: Vec3| x.dot(s.to_mat3x3() * x);
+
+        let (min, argmin) = min_on_sphere(&s);
+        assert_eq!(min, values.z);
+        assert!(argmin.abs_diff_eq(vectors.z_axis, 1e-6));
+        approx::assert_relative_eq!(argmin.length(), 1.0, epsilon = 1e-5);
+        approx::assert_relative_eq!(quadratic(argmin), min, epsilon = 1e-4);
+
+        let (max, argmax) = max_on_sphere(&s);
+        assert_eq!(max, values.x);
+        approx::assert_relative_eq!(quadratic(argmax), max, epsilon = 1e-4);
+
+        // no direction does better than the extremes
+        for i in 0..20 {
+            let t = i as f32;
+            let x = Vec3::new(t.sin(), (0.7 * t).cos(), 0.3).normalize();
+            assert!(quadratic(x) >= min - 1e-4 && quadratic(x) <= max + 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_refine_eigenvector() {
+        let s = Symmetric3x3::from_upper_triangular(&[4.0, 1.0, 0.5, 3.0, -0.25, 1.0]);
+        let m = s.to_mat3x3();
+        let (values, vectors) = symmetric_eigen(&s);
+
+        for i in 0..3 {
+            let lambda = values[i];
+            let v = (vectors.col(i) + Vec3::new(0.02, -0.03, 0.01)).normalize();
+            let residual = |v: Vec3| (m * v - lambda * v).length();
+
+            let refined = refine_eigenvector(&s, lambda, v);
+            approx::assert_relative_eq!(refined.length(), 1.0, epsilon = 1e-5);
+            assert!(refined.dot(v) > 0.0);
+            assert!(residual(refined) < 0.1 * residual(v));
+        }
+
+        // an exact eigenpair is left untouched
+        let refined = refine_eigenvector(&Symmetric3x3::IDENTITY, 1.0, Vec3::new(0.0, 2.0, 0.0));
+        assert_eq!(refined, Vec3::Y);
+    }
 }